@@ -68,7 +68,10 @@ pub fn bindgen_macro(cx: &mut base::ExtCtxt, sp: codemap::Span, tts: &[ast::Toke
             Box::new(BindgenResult { items: Some(SmallVector::many(items)) }) as Box<base::MacResult>
             
         }
-        Err(_) => base::DummyResult::any(sp)
+        Err(e) => {
+            logger.error(&format!("Unable to generate bindings. {}", e)[..]);
+            base::DummyResult::any(sp)
+        }
     };
 
     let p = Path::new(&cwd);
@@ -102,6 +105,15 @@ impl MacroArgsVisitor for BindgenArgsVisitor {
             Some("match") => self.options.match_pat.push(val.to_string()),
             Some("clang_args") => self.options.clang_args.push(val.to_string()),
             Some("enum_type") => self.options.override_enum_ty = val.to_string(),
+            Some("enum_style") => return self.options.parse_enum_style(val).is_ok(),
+            Some("allowlist_function") => self.options.allowlist_functions.push(val.to_string()),
+            Some("blocklist_function") => self.options.blocklist_functions.push(val.to_string()),
+            Some("allowlist_type") => self.options.allowlist_types.push(val.to_string()),
+            Some("blocklist_type") => self.options.blocklist_types.push(val.to_string()),
+            Some("allowlist_var") => self.options.allowlist_vars.push(val.to_string()),
+            Some("blocklist_var") => self.options.blocklist_vars.push(val.to_string()),
+            Some("opaque_type") => self.options.opaque_types.push(val.to_string()),
+            Some("replace_type") => return self.options.parse_type_replacement(val).is_ok(),
             _ => return false
         }
         true
@@ -117,6 +129,8 @@ impl MacroArgsVisitor for BindgenArgsVisitor {
         match name {
             Some("allow_unknown_types") => self.options.fail_on_unknown_type = !val,
             Some("emit_builtins") => self.options.builtins = val,
+            Some("layout_tests") => self.options.layout_tests = val,
+            Some("opaque_behind_pointers") => self.options.opaque_behind_pointers = val,
             _ => return false
         }
         true
@@ -4,7 +4,7 @@
 extern crate bindgen;
 #[macro_use] extern crate log;
 
-use bindgen::{Bindings, BindgenOptions, LinkType, Logger};
+use bindgen::{Bindings, BindgenOptions, Diagnostic, LinkType, Logger, Severity};
 use std::io;
 use std::path;
 use std::env;
@@ -23,11 +23,20 @@ impl Logger for StdLogger {
     fn warn(&self, msg: &str) {
         warn!("{}", msg);
     }
+
+    fn log(&self, diag: &Diagnostic) {
+        match diag.severity {
+            Severity::Debug => debug!("{}", diag),
+            Severity::Info | Severity::Note => info!("{}", diag),
+            Severity::Warning => warn!("{}", diag),
+            Severity::Error | Severity::Fatal => error!("{}", diag),
+        }
+    }
 }
 
 enum ParseResult {
     CmdUsage,
-    ParseOk(BindgenOptions, Box<io::Write+'static>),
+    ParseOk(BindgenOptions, Box<io::Write+'static>, Option<String>),
     ParseErr(String)
 }
 
@@ -36,6 +45,7 @@ fn parse_args(args: &[String]) -> ParseResult {
 
     let mut options: BindgenOptions = Default::default();
     let mut out = Box::new(io::BufWriter::new(io::stdout())) as Box<io::Write>;
+    let mut shim = None;
 
     if args_len == 0 {
         return ParseResult::CmdUsage;
@@ -94,6 +104,48 @@ fn parse_args(args: &[String]) -> ParseResult {
                     options.match_pat.push(args[ix + 1].clone());
                     ix += 2;
                 }
+                "-allowlist-function" => {
+                    if ix + 1 >= args_len {
+                        return ParseResult::ParseErr("Missing function pattern".to_string());
+                    }
+                    options.allowlist_functions.push(args[ix + 1].clone());
+                    ix += 2;
+                }
+                "-blocklist-function" => {
+                    if ix + 1 >= args_len {
+                        return ParseResult::ParseErr("Missing function pattern".to_string());
+                    }
+                    options.blocklist_functions.push(args[ix + 1].clone());
+                    ix += 2;
+                }
+                "-allowlist-type" => {
+                    if ix + 1 >= args_len {
+                        return ParseResult::ParseErr("Missing type pattern".to_string());
+                    }
+                    options.allowlist_types.push(args[ix + 1].clone());
+                    ix += 2;
+                }
+                "-blocklist-type" => {
+                    if ix + 1 >= args_len {
+                        return ParseResult::ParseErr("Missing type pattern".to_string());
+                    }
+                    options.blocklist_types.push(args[ix + 1].clone());
+                    ix += 2;
+                }
+                "-allowlist-var" => {
+                    if ix + 1 >= args_len {
+                        return ParseResult::ParseErr("Missing variable pattern".to_string());
+                    }
+                    options.allowlist_vars.push(args[ix + 1].clone());
+                    ix += 2;
+                }
+                "-blocklist-var" => {
+                    if ix + 1 >= args_len {
+                        return ParseResult::ParseErr("Missing variable pattern".to_string());
+                    }
+                    options.blocklist_vars.push(args[ix + 1].clone());
+                    ix += 2;
+                }
                 "-builtins" => {
                     options.builtins = true;
                     ix += 1;
@@ -102,6 +154,61 @@ fn parse_args(args: &[String]) -> ParseResult {
                     options.fail_on_unknown_type = false;
                     ix += 1;
                 }
+                "-replace-type" => {
+                    if ix + 1 >= args_len {
+                        return ParseResult::ParseErr("Missing type replacement".to_string());
+                    }
+                    if options.parse_type_replacement(&args[ix + 1][..]).is_err() {
+                        return ParseResult::ParseErr(format!("Invalid type replacement {}", args[ix + 1]));
+                    }
+                    ix += 2;
+                }
+                "-opaque-type" => {
+                    if ix + 1 >= args_len {
+                        return ParseResult::ParseErr("Missing opaque type pattern".to_string());
+                    }
+                    options.opaque_types.push(args[ix + 1].clone());
+                    ix += 2;
+                }
+                "-opaque-behind-pointers" => {
+                    options.opaque_behind_pointers = true;
+                    ix += 1;
+                }
+                "-wrap-static-fns" => {
+                    if ix + 1 >= args_len {
+                        return ParseResult::ParseErr("Missing shim filename".to_string());
+                    }
+                    options.wrap_static_fns = true;
+                    shim = Some(args[ix + 1].clone());
+                    ix += 2;
+                }
+                "-wrap-macro" => {
+                    if ix + 1 >= args_len {
+                        return ParseResult::ParseErr("Missing macro prototype".to_string());
+                    }
+                    options.wrap_macros.push(args[ix + 1].clone());
+                    ix += 2;
+                }
+                "-shim" => {
+                    if ix + 1 >= args_len {
+                        return ParseResult::ParseErr("Missing shim filename".to_string());
+                    }
+                    shim = Some(args[ix + 1].clone());
+                    ix += 2;
+                }
+                "-layout-tests" => {
+                    options.layout_tests = true;
+                    ix += 1;
+                }
+                "-enum-style" => {
+                    if ix + 1 >= args_len {
+                        return ParseResult::ParseErr("Missing enum style".to_string());
+                    }
+                    if options.parse_enum_style(&args[ix + 1][..]).is_err() {
+                        return ParseResult::ParseErr(format!("Invalid enum style {}", args[ix + 1]));
+                    }
+                    ix += 2;
+                }
                 "-override-enum-type" => {
                     if ix + 1 >= args_len {
                         return ParseResult::ParseErr("Missing enum type".to_string());
@@ -117,7 +224,7 @@ fn parse_args(args: &[String]) -> ParseResult {
         }
     }
 
-    return ParseResult::ParseOk(options, out);
+    return ParseResult::ParseOk(options, out, shim);
 }
 
 fn print_usage(bin: String) {
@@ -135,11 +242,59 @@ Options:
                                whose name contains <name>
                                If multiple -match options are provided, files
                                matching any rule are bound to.
+    -allowlist-function <regex>
+                               Only output bindings for functions whose name
+                               matches <regex>, can be provided multiple times
+    -blocklist-function <regex>
+                               Don't output bindings for functions whose name
+                               matches <regex>, can be provided multiple times
+    -allowlist-type <regex>    Same as -allowlist-function for structs, unions,
+                               enums and typedefs
+    -blocklist-type <regex>    Same as -blocklist-function for structs, unions,
+                               enums and typedefs
+    -allowlist-var <regex>     Same as -allowlist-function for variables and
+                               #define constants
+    -blocklist-var <regex>     Same as -blocklist-function for variables and
+                               #define constants
+                               Once anything is allowlisted, only allowlisted
+                               items and the types they need are output
+    -opaque-behind-pointers    Output the structs and unions only needed
+                               behind a pointer as opaque types
+    -opaque-type <regex>       Output structs and unions whose name matches
+                               <regex> as a blob of the right size, without
+                               their fields, can be provided multiple times
+    -replace-type <name>=<type>
+                               Use the Rust type <type> wherever the C type
+                               <name> is referred to, instead of generating
+                               it, can be provided multiple times
     -builtins                  Output bindings for builtin definitions
                                (for example __builtin_va_list)
     -allow-unknown-types       Don't fail if we encounter types we do not support,
                                instead treat them as void
     -emit-clang-ast            Output the ast (for debugging purposes)
+    -layout-tests              Output tests checking the size, alignment and
+                               field offsets of structs and unions
+    -wrap-static-fns <shim.c>  Output bindings for static functions, including
+                               static inline ones, calling exported wrappers
+                               written to <shim.c>, which has to be compiled
+                               and linked
+    -wrap-macro <prototype>    Output bindings for the function-like macro
+                               declared by <prototype>, e.g.
+                               "int FOO_GET(struct foo *f)", calling an
+                               exported wrapper written to the shim, can be
+                               provided multiple times
+    -shim <shim.c>             Write the wrappers of macros to <shim.c> when
+                               static functions aren't wrapped
+    -enum-style [<pattern>=]<style>
+                               Generate enums in the given style, only for
+                               enums whose name matches the <pattern> regex
+                               if given.
+                               Can be provided multiple times, style could be
+                                 consts (default)
+                                 rust
+                                 module
+                                 newtype
+                                 bitflags
     -override-enum-type <type> Override enum type, type name could be
                                  uchar
                                  schar
@@ -173,17 +328,28 @@ pub fn main() {
     match parse_args(&bind_args[..]) {
         ParseResult::ParseErr(e) => panic!(e),
         ParseResult::CmdUsage => print_usage(bin),
-        ParseResult::ParseOk(options, out) => {
+        ParseResult::ParseOk(options, out, shim) => {
             let logger = StdLogger;
             match Bindings::generate(&options, Some(&logger as &Logger), None) {
-                Ok(bindings) => match bindings.write(out) {
-                    Ok(()) => (),
-                    Err(e) => {
-                        logger.error(&format!("Unable to write bindings to file. {}", e)[..]);
-                        exit(-1);
+                Ok(bindings) => {
+                    match bindings.write(out) {
+                        Ok(()) => (),
+                        Err(e) => {
+                            logger.error(&format!("Unable to write bindings to file. {}", e)[..]);
+                            exit(-1);
+                        }
+                    }
+                    if let Some(path) = shim {
+                        if let Err(e) = bindings.write_static_fns_shim(&path) {
+                            logger.error(&format!("Unable to write shim to file. {}", e)[..]);
+                            exit(-1);
+                        }
                     }
                 },
-                Err(()) => exit(-1)
+                Err(e) => {
+                    logger.error(&format!("Unable to generate bindings. {}", e)[..]);
+                    exit(-1);
+                }
             }
         }
     }
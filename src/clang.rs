@@ -1,7 +1,7 @@
 #![allow(non_upper_case_globals, dead_code)]
 
 use libc::{c_uint, c_char, c_int, c_ulong};
-use std::{mem, ptr};
+use std::{mem, ptr, slice};
 use std::fmt;
 use std::str;
 use std::ffi::CStr;
@@ -39,6 +39,12 @@ impl Cursor {
         }
     }
 
+    pub fn extent(&self) -> CXSourceRange {
+        unsafe {
+            clang_getCursorExtent(self.x)
+        }
+    }
+
     pub fn cur_type(&self) -> Type {
         unsafe {
             Type { x: clang_getCursorType(self.x) }
@@ -51,12 +57,42 @@ impl Cursor {
         }
     }
 
+    pub fn raw_comment(&self) -> String {
+        unsafe {
+            String_ { x: clang_Cursor_getRawCommentText(self.x) }.to_string()
+        }
+    }
+
+    pub fn comment(&self) -> Comment {
+        unsafe {
+            Comment { x: clang_Cursor_getParsedComment(self.x) }
+        }
+    }
+
     pub fn canonical(&self) -> Cursor {
         unsafe {
             Cursor { x: clang_getCanonicalCursor(self.x) }
         }
     }
 
+    pub fn semantic_parent(&self) -> Cursor {
+        unsafe {
+            Cursor { x: clang_getCursorSemanticParent(self.x) }
+        }
+    }
+
+    pub fn mangling(&self) -> String {
+        unsafe {
+            String_ { x: clang_Cursor_getMangling(self.x) }.to_string()
+        }
+    }
+
+    pub fn translation_unit(&self) -> TranslationUnit {
+        unsafe {
+            TranslationUnit { x: clang_Cursor_getTranslationUnit(self.x) }
+        }
+    }
+
     pub fn visit<F>(&self, func:F)
         where F: for<'a, 'b> FnMut(&'a Cursor, &'b Cursor) -> Enum_CXChildVisitResult
     {
@@ -79,6 +115,16 @@ impl Cursor {
         }
     }
 
+    /// The offset in bits of a field from the start of the record it is
+    /// declared in.  Unlike `Type::offset_of` this also works for unnamed
+    /// bitfields.
+    pub fn offset_of_field(&self) -> Option<usize> {
+        unsafe {
+            let val = clang_Cursor_getOffsetOfField(self.x);
+            if val < 0 { None } else { Some(val as usize) }
+        }
+    }
+
     // enum
     pub fn enum_type(&self) -> Type {
         unsafe {
@@ -92,6 +138,12 @@ impl Cursor {
         }
     }
 
+    pub fn enum_val_unsigned(&self) -> u64 {
+        unsafe {
+            clang_getEnumConstantDeclUnsignedValue(self.x) as u64
+        }
+    }
+
     // typedef
     pub fn typedef_type(&self) -> Type {
         unsafe {
@@ -106,6 +158,24 @@ impl Cursor {
         }
     }
 
+    pub fn is_deprecated(&self) -> bool {
+        unsafe {
+            clang_getCursorAvailability(self.x) == CXAvailability_Deprecated
+        }
+    }
+
+    /// The message given to the deprecated attribute, if any.
+    pub fn deprecated_message(&self) -> String {
+        unsafe {
+            let mut always_deprecated = 0;
+            let mut message: CXString = mem::zeroed();
+            clang_getCursorPlatformAvailability(self.x, &mut always_deprecated, &mut message,
+                                                ptr::null_mut(), ptr::null_mut(),
+                                                ptr::null_mut(), 0);
+            String_ { x: message }.to_string()
+        }
+    }
+
     // function
     pub fn args(&self) -> Vec<Cursor> {
         unsafe {
@@ -129,6 +199,56 @@ impl Cursor {
             clang_Cursor_getNumArguments(self.x)
         }
     }
+
+    // C++ member
+    pub fn access_specifier(&self) -> Enum_CX_CXXAccessSpecifier {
+        unsafe {
+            clang_getCXXAccessSpecifier(self.x)
+        }
+    }
+
+    pub fn method_is_static(&self) -> bool {
+        unsafe {
+            clang_CXXMethod_isStatic(self.x) != 0
+        }
+    }
+
+    pub fn method_is_const(&self) -> bool {
+        unsafe {
+            clang_CXXMethod_isConst(self.x) != 0
+        }
+    }
+
+    pub fn method_is_virtual(&self) -> bool {
+        unsafe {
+            clang_CXXMethod_isVirtual(self.x) != 0
+        }
+    }
+
+    pub fn method_is_override(&self) -> bool {
+        unsafe {
+            let mut overridden = ptr::null_mut();
+            let mut num_overridden = 0;
+            clang_getOverriddenCursors(self.x, &mut overridden, &mut num_overridden);
+            if !overridden.is_null() {
+                clang_disposeOverriddenCursors(overridden);
+            }
+            num_overridden > 0
+        }
+    }
+
+    pub fn is_virtual_base(&self) -> bool {
+        unsafe {
+            clang_isVirtualBase(self.x) != 0
+        }
+    }
+
+    // ObjC property
+    pub fn objc_property_attributes(&self) -> c_uint {
+        unsafe {
+            clang_Cursor_getObjCPropertyAttributes(self.x, 0)
+        }
+    }
 }
 
 extern fn visit_children(cur: CXCursor, parent: CXCursor,
@@ -178,6 +298,12 @@ impl Type {
         }
     }
 
+    pub fn spelling(&self) -> String {
+        unsafe {
+            String_ { x: clang_getTypeSpelling(self.x) }.to_string()
+        }
+    }
+
     pub fn is_const(&self) -> bool {
         unsafe {
             clang_isConstQualifiedType(self.x) == 1
@@ -198,6 +324,15 @@ impl Type {
         }
     }
 
+    // record
+    pub fn offset_of(&self, field: &str) -> Option<usize> {
+        unsafe {
+            let name = CString::new(field.as_bytes()).unwrap();
+            let val = clang_Type_getOffsetOf(self.x, name.as_ptr());
+            if val < 0 { None } else { Some(val as usize) }
+        }
+    }
+
     // pointer
     pub fn pointee_type(&self) -> Type {
         unsafe {
@@ -212,6 +347,13 @@ impl Type {
         }
     }
 
+    // complex
+    pub fn complex_elem_type(&self) -> Type {
+        unsafe {
+            Type { x: clang_getElementType(self.x) }
+        }
+    }
+
     pub fn array_size(&self) -> usize {
         unsafe {
             clang_getArraySize(self.x) as usize
@@ -290,6 +432,113 @@ impl fmt::Display for SourceLocation {
     }
 }
 
+// Comment
+pub struct Comment {
+    x: CXComment
+}
+
+impl Comment {
+    pub fn kind(&self) -> Enum_CXCommentKind {
+        unsafe {
+            clang_Comment_getKind(self.x)
+        }
+    }
+
+    pub fn children(&self) -> Vec<Comment> {
+        unsafe {
+            let num = clang_Comment_getNumChildren(self.x) as usize;
+            let mut children = vec!();
+            for i in 0..num {
+                children.push(Comment { x: clang_Comment_getChild(self.x, i as c_uint) });
+            }
+            return children;
+        }
+    }
+
+    pub fn is_whitespace(&self) -> bool {
+        unsafe {
+            clang_Comment_isWhitespace(self.x) != 0
+        }
+    }
+
+    pub fn has_trailing_newline(&self) -> bool {
+        unsafe {
+            clang_InlineContentComment_hasTrailingNewline(self.x) != 0
+        }
+    }
+
+    // text
+    pub fn text(&self) -> String {
+        unsafe {
+            String_ { x: clang_TextComment_getText(self.x) }.to_string()
+        }
+    }
+
+    // inline command
+    pub fn inline_command_name(&self) -> String {
+        unsafe {
+            String_ { x: clang_InlineCommandComment_getCommandName(self.x) }.to_string()
+        }
+    }
+
+    pub fn inline_render_kind(&self) -> Enum_CXCommentInlineCommandRenderKind {
+        unsafe {
+            clang_InlineCommandComment_getRenderKind(self.x)
+        }
+    }
+
+    pub fn inline_args(&self) -> Vec<String> {
+        unsafe {
+            let num = clang_InlineCommandComment_getNumArgs(self.x) as usize;
+            let mut args = vec!();
+            for i in 0..num {
+                args.push(String_ { x: clang_InlineCommandComment_getArgText(self.x, i as c_uint) }.to_string());
+            }
+            return args;
+        }
+    }
+
+    // html tag
+    pub fn html_string(&self) -> String {
+        unsafe {
+            String_ { x: clang_HTMLTagComment_getAsString(self.x) }.to_string()
+        }
+    }
+
+    // block command
+    pub fn block_command_name(&self) -> String {
+        unsafe {
+            String_ { x: clang_BlockCommandComment_getCommandName(self.x) }.to_string()
+        }
+    }
+
+    pub fn block_paragraph(&self) -> Comment {
+        unsafe {
+            Comment { x: clang_BlockCommandComment_getParagraph(self.x) }
+        }
+    }
+
+    // param command
+    pub fn param_name(&self) -> String {
+        unsafe {
+            String_ { x: clang_ParamCommandComment_getParamName(self.x) }.to_string()
+        }
+    }
+
+    // verbatim
+    pub fn verbatim_block_line_text(&self) -> String {
+        unsafe {
+            String_ { x: clang_VerbatimBlockLineComment_getText(self.x) }.to_string()
+        }
+    }
+
+    pub fn verbatim_line_text(&self) -> String {
+        unsafe {
+            String_ { x: clang_VerbatimLineComment_getText(self.x) }.to_string()
+        }
+    }
+}
+
 // File
 pub struct File {
     x: CXFile
@@ -403,6 +652,40 @@ impl TranslationUnit {
         }
     }
 
+    // Returns the tokens that make up the given cursor. Some versions of
+    // libclang include the token following the cursor in its extent, so
+    // anything starting at or after the end of the extent is dropped.
+    pub fn tokens(&self, cursor: &Cursor) -> Option<Vec<Token>> {
+        let range = cursor.extent();
+        let (_, _, _, end) = unsafe {
+            SourceLocation { x: clang_getRangeEnd(range) }.location()
+        };
+        let mut tokens = vec!();
+        unsafe {
+            let mut token_ptr = ptr::null_mut();
+            let mut num_tokens: c_uint = 0;
+            clang_tokenize(self.x, range, &mut token_ptr, &mut num_tokens);
+            if token_ptr.is_null() {
+                return None;
+            }
+            let token_array = slice::from_raw_parts(token_ptr, num_tokens as usize);
+            for &token in token_array.iter() {
+                let loc = SourceLocation { x: clang_getTokenLocation(self.x, token) };
+                let (_, _, _, offset) = loc.location();
+                if offset >= end {
+                    continue;
+                }
+                tokens.push(Token {
+                    kind: clang_getTokenKind(token),
+                    spelling: String_ { x: clang_getTokenSpelling(self.x, token) }.to_string(),
+                    offset: offset
+                });
+            }
+            clang_disposeTokens(self.x, token_ptr, num_tokens);
+        }
+        Some(tokens)
+    }
+
     pub fn dispose(&self) {
         unsafe {
             clang_disposeTranslationUnit(self.x);
@@ -414,6 +697,13 @@ impl TranslationUnit {
     }
 }
 
+// Token
+pub struct Token {
+    pub kind: Enum_CXTokenKind,
+    pub spelling: String,
+    pub offset: usize
+}
+
 // Diagnostic
 pub struct Diagnostic {
     x: CXDiagnostic
@@ -438,6 +728,18 @@ impl Diagnostic {
         }
     }
 
+    pub fn spelling(&self) -> String {
+        unsafe {
+            String_ { x: clang_getDiagnosticSpelling(self.x) }.to_string()
+        }
+    }
+
+    pub fn location(&self) -> SourceLocation {
+        unsafe {
+            SourceLocation { x: clang_getDiagnosticLocation(self.x) }
+        }
+    }
+
     pub fn dispose(&self) {
         unsafe {
             clang_disposeDiagnostic(self.x);
@@ -622,6 +924,8 @@ pub fn kind_to_str(x: Enum_CXCursorKind) -> &'static str {
         CXCursor_CXXOverrideAttr => "CXXOverrideAttr",
         CXCursor_AnnotateAttr => "AnnotateAttr",
         CXCursor_AsmLabelAttr => "AsmLabelAttr",
+        CXCursor_VisibilityAttr => "VisibilityAttr",
+        CXCursor_WarnUnusedResultAttr => "WarnUnusedResultAttr",
         // CXCursor_LastAttr => "LastAttr",
         CXCursor_PreprocessingDirective => "PreprocessingDirective",
         CXCursor_MacroDefinition => "MacroDefinition",
@@ -690,6 +994,27 @@ pub fn type_to_str(x: Enum_CXTypeKind) -> &'static str {
     }
 }
 
+pub fn call_conv_to_str(x: Enum_CXCallingConv) -> &'static str {
+    match x {
+        CXCallingConv_Default => "Default",
+        CXCallingConv_C => "C",
+        CXCallingConv_X86StdCall => "X86StdCall",
+        CXCallingConv_X86FastCall => "X86FastCall",
+        CXCallingConv_X86ThisCall => "X86ThisCall",
+        CXCallingConv_X86Pascal => "X86Pascal",
+        CXCallingConv_AAPCS => "AAPCS",
+        CXCallingConv_AAPCS_VFP => "AAPCS_VFP",
+        CXCallingConv_PnaclCall => "PnaclCall",
+        CXCallingConv_IntelOclBicc => "IntelOclBicc",
+        CXCallingConv_X86_64Win64 => "X86_64Win64",
+        CXCallingConv_X86_64SysV => "X86_64SysV",
+        CXCallingConv_X86VectorCall => "X86VectorCall",
+        CXCallingConv_Invalid => "Invalid",
+        CXCallingConv_Unexposed => "Unexposed",
+        _ => "?"
+    }
+}
+
 // Debug
 pub fn ast_dump(c: &Cursor, depth: isize)-> Enum_CXVisitorResult {
     fn print_indent(depth: isize, s: &str) {
@@ -303,7 +303,40 @@ pub const CXCursor_CXXOverrideAttr: ::libc::c_uint = 405;
 pub const CXCursor_AnnotateAttr: ::libc::c_uint = 406;
 pub const CXCursor_AsmLabelAttr: ::libc::c_uint = 407;
 pub const CXCursor_PackedAttr: ::libc::c_uint = 408;
-pub const CXCursor_LastAttr: ::libc::c_uint = 408;
+pub const CXCursor_PureAttr: ::libc::c_uint = 409;
+pub const CXCursor_ConstAttr: ::libc::c_uint = 410;
+pub const CXCursor_NoDuplicateAttr: ::libc::c_uint = 411;
+pub const CXCursor_CUDAConstantAttr: ::libc::c_uint = 412;
+pub const CXCursor_CUDADeviceAttr: ::libc::c_uint = 413;
+pub const CXCursor_CUDAGlobalAttr: ::libc::c_uint = 414;
+pub const CXCursor_CUDAHostAttr: ::libc::c_uint = 415;
+pub const CXCursor_CUDASharedAttr: ::libc::c_uint = 416;
+pub const CXCursor_VisibilityAttr: ::libc::c_uint = 417;
+pub const CXCursor_DLLExport: ::libc::c_uint = 418;
+pub const CXCursor_DLLImport: ::libc::c_uint = 419;
+pub const CXCursor_NSReturnsRetained: ::libc::c_uint = 420;
+pub const CXCursor_NSReturnsNotRetained: ::libc::c_uint = 421;
+pub const CXCursor_NSReturnsAutoreleased: ::libc::c_uint = 422;
+pub const CXCursor_NSConsumesSelf: ::libc::c_uint = 423;
+pub const CXCursor_NSConsumed: ::libc::c_uint = 424;
+pub const CXCursor_ObjCException: ::libc::c_uint = 425;
+pub const CXCursor_ObjCNSObject: ::libc::c_uint = 426;
+pub const CXCursor_ObjCIndependentClass: ::libc::c_uint = 427;
+pub const CXCursor_ObjCPreciseLifetime: ::libc::c_uint = 428;
+pub const CXCursor_ObjCReturnsInnerPointer: ::libc::c_uint = 429;
+pub const CXCursor_ObjCRequiresSuper: ::libc::c_uint = 430;
+pub const CXCursor_ObjCRootClass: ::libc::c_uint = 431;
+pub const CXCursor_ObjCSubclassingRestricted: ::libc::c_uint = 432;
+pub const CXCursor_ObjCExplicitProtocolImpl: ::libc::c_uint = 433;
+pub const CXCursor_ObjCDesignatedInitializer: ::libc::c_uint = 434;
+pub const CXCursor_ObjCRuntimeVisible: ::libc::c_uint = 435;
+pub const CXCursor_ObjCBoxable: ::libc::c_uint = 436;
+pub const CXCursor_FlagEnum: ::libc::c_uint = 437;
+pub const CXCursor_ConvergentAttr: ::libc::c_uint = 438;
+pub const CXCursor_WarnUnusedAttr: ::libc::c_uint = 439;
+pub const CXCursor_WarnUnusedResultAttr: ::libc::c_uint = 440;
+pub const CXCursor_AlignedAttr: ::libc::c_uint = 441;
+pub const CXCursor_LastAttr: ::libc::c_uint = 441;
 pub const CXCursor_PreprocessingDirective: ::libc::c_uint = 500;
 pub const CXCursor_MacroDefinition: ::libc::c_uint = 501;
 pub const CXCursor_MacroExpansion: ::libc::c_uint = 502;
@@ -415,6 +448,7 @@ pub const CXCallingConv_PnaclCall: ::libc::c_uint = 8;
 pub const CXCallingConv_IntelOclBicc: ::libc::c_uint = 9;
 pub const CXCallingConv_X86_64Win64: ::libc::c_uint = 10;
 pub const CXCallingConv_X86_64SysV: ::libc::c_uint = 11;
+pub const CXCallingConv_X86VectorCall: ::libc::c_uint = 12;
 pub const CXCallingConv_Invalid: ::libc::c_uint = 100;
 pub const CXCallingConv_Unexposed: ::libc::c_uint = 200;
 #[repr(C)]
@@ -1081,6 +1115,7 @@ extern "C" {
     pub fn clang_Type_getCXXRefQualifier(T: CXType) ->
      Enum_CXRefQualifierKind;
     pub fn clang_Cursor_isBitField(C: CXCursor) -> ::libc::c_uint;
+    pub fn clang_Cursor_getOffsetOfField(C: CXCursor) -> ::libc::c_longlong;
     pub fn clang_isVirtualBase(arg1: CXCursor) -> ::libc::c_uint;
     pub fn clang_getCXXAccessSpecifier(arg1: CXCursor) ->
      Enum_CX_CXXAccessSpecifier;
@@ -1131,6 +1166,7 @@ extern "C" {
     pub fn clang_Cursor_getBriefCommentText(C: CXCursor) -> CXString;
     pub fn clang_Cursor_getParsedComment(C: CXCursor) -> CXComment;
     pub fn clang_Cursor_getModule(C: CXCursor) -> CXModule;
+    pub fn clang_Cursor_getMangling(C: CXCursor) -> CXString;
     pub fn clang_Module_getASTFile(Module: CXModule) -> CXFile;
     pub fn clang_Module_getParent(Module: CXModule) -> CXModule;
     pub fn clang_Module_getName(Module: CXModule) -> CXString;
@@ -1206,6 +1242,7 @@ extern "C" {
     pub fn clang_CXXMethod_isPureVirtual(C: CXCursor) -> ::libc::c_uint;
     pub fn clang_CXXMethod_isStatic(C: CXCursor) -> ::libc::c_uint;
     pub fn clang_CXXMethod_isVirtual(C: CXCursor) -> ::libc::c_uint;
+    pub fn clang_CXXMethod_isConst(C: CXCursor) -> ::libc::c_uint;
     pub fn clang_getTemplateCursorKind(C: CXCursor) -> Enum_CXCursorKind;
     pub fn clang_getSpecializedCursorTemplate(C: CXCursor) -> CXCursor;
     pub fn clang_getCursorReferenceNameRange(C: CXCursor,
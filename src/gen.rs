@@ -1,4 +1,6 @@
 use std::cell::RefCell;
+use std::cmp;
+use std::mem;
 use std::vec::Vec;
 use std::rc::Rc;
 use std::collections::HashMap;
@@ -14,16 +16,32 @@ use syntax::ext::quote::rt::ToTokens;
 use syntax::feature_gate::Features;
 use syntax::owned_slice::OwnedSlice;
 use syntax::parse;
+use syntax::parse::token;
 use syntax::attr::mk_attr_id;
 use syntax::ptr::P;
-use syntax::print::pprust::tts_to_string;
+use syntax::print::pprust::{pat_to_string, tts_to_string, ty_to_string};
 
-use super::LinkType;
+use super::{BindgenOptions, Category, Diagnostic, EnumStyle, LinkType, Logger, Severity};
+use parser::NameFilter;
 use types::*;
 
 struct GenCtx<'r> {
     ext_cx: base::ExtCtxt<'r>,
     unnamed_ty: usize,
+    // Layout of `long double`, once a use of it requires the blob type
+    long_double: Option<Layout>,
+    // Whether a `_Complex` type requires the complex pair type
+    complex: bool,
+    // Whether an Objective-C object type requires `id`
+    objc: bool,
+    // The C++ namespace of the items being generated
+    module: Vec<String>,
+    // The enum styles chosen by name, in the order they were given
+    enum_styles: Vec<(NameFilter, EnumStyle)>,
+    // The Rust types replacing C types, by C name
+    type_replacements: Vec<(String, P<ast::Ty>)>,
+    options: &'r BindgenOptions,
+    logger: &'r Logger,
     span: Span
 }
 
@@ -113,7 +131,9 @@ fn enum_name(name: &String) -> String {
     format!("Enum_{}", name)
 }
 
-pub fn gen_mod(links: &[(String, LinkType)], globs: Vec<Global>, span: Span) -> Vec<P<ast::Item>> {
+pub fn gen_mod(options: &BindgenOptions, enum_styles: Vec<(NameFilter, EnumStyle)>,
+               type_replacements: Vec<(String, P<ast::Ty>)>, globs: Vec<Global>,
+               logger: &Logger, span: Span) -> Vec<P<ast::Item>> {
     // Create a dummy ExtCtxt. We only need this for string interning and that uses TLS.
     let mut features = Features::new();
     features.allow_quote = true;
@@ -131,6 +151,14 @@ pub fn gen_mod(links: &[(String, LinkType)], globs: Vec<Global>, span: Span) ->
             cfg,
         ),
         unnamed_ty: 0,
+        long_double: None,
+        complex: false,
+        objc: false,
+        module: vec!(),
+        enum_styles: enum_styles,
+        type_replacements: type_replacements,
+        options: options,
+        logger: logger,
         span: span
     };
     ctx.ext_cx.bt_push(ExpnInfo {
@@ -142,15 +170,107 @@ pub fn gen_mod(links: &[(String, LinkType)], globs: Vec<Global>, span: Span) ->
             span: None
         }
     });
+
+    // Declarations in C++ namespaces go in nested modules, generated one
+    // namespace at a time so that references can be made relative.
+    let mut namespaces: Vec<(Vec<String>, Vec<Global>)> = vec!();
+    for g in globs.into_iter() {
+        let ns = g.namespace();
+        let pos = namespaces.iter().position(|&(ref n, _)| *n == ns);
+        match pos {
+            Some(i) => namespaces[i].1.push(g),
+            None => namespaces.push((ns, vec!(g)))
+        }
+    }
+
+    let mut root = ModTree::new();
+    for (ns, globs) in namespaces.into_iter() {
+        ctx.module = ns.clone();
+        let items = gen_globals(&mut ctx, globs);
+        root.insert(&ns[..], items);
+    }
+    ctx.module = vec!();
+    let defs = root.into_items(&mut ctx);
+    for &(ref filter, _) in ctx.enum_styles.iter() {
+        filter.report_unused(logger);
+    }
+
+    // Types standing in for C types that have no Rust equivalent go first
+    let mut helpers = vec!();
+    if let Some(layout) = ctx.long_double {
+        helpers.extend(mk_long_double(&mut ctx, layout).into_iter());
+    }
+    if ctx.complex {
+        helpers.push(mk_complex(&mut ctx));
+    }
+    if ctx.objc {
+        helpers.push(mk_objc_id(&mut ctx));
+    }
+    helpers.extend(defs.into_iter());
+    let defs = helpers;
+
+    //let attrs = vec!(mk_attr_list(&mut ctx, "allow", ["dead_code", "non_camel_case_types", "uppercase_variables"]));
+
+    defs
+}
+
+/// The items generated for a namespace and the namespaces nested in it.
+struct ModTree {
+    items: Vec<P<ast::Item>>,
+    children: Vec<(String, ModTree)>,
+}
+
+impl ModTree {
+    fn new() -> ModTree {
+        ModTree { items: vec!(), children: vec!() }
+    }
+
+    fn insert(&mut self, ns: &[String], items: Vec<P<ast::Item>>) {
+        if ns.is_empty() {
+            self.items.extend(items.into_iter());
+            return;
+        }
+        let pos = self.children.iter().position(|&(ref name, _)| *name == ns[0]);
+        let i = match pos {
+            Some(i) => i,
+            None => {
+                self.children.push((ns[0].clone(), ModTree::new()));
+                self.children.len() - 1
+            }
+        };
+        self.children[i].1.insert(&ns[1..], items);
+    }
+
+    fn into_items(self, ctx: &mut GenCtx) -> Vec<P<ast::Item>> {
+        let mut items = self.items;
+        for (name, tree) in self.children.into_iter() {
+            let name = first(rust_id(ctx, name));
+            let id = ctx.ext_cx.ident_of(&name[..]);
+            let mod_items = tree.into_items(ctx);
+            let module = ctx.ext_cx.item_mod(ctx.span, ctx.span, id, vec!(), mod_items);
+            items.push(module.map(|mut m| { m.vis = ast::Public; m }));
+        }
+        items
+    }
+}
+
+fn gen_globals(ctx: &mut GenCtx, globs: Vec<Global>) -> Vec<P<ast::Item>> {
+    let options = ctx.options;
     let uniq_globs = tag_dup_decl(globs);
 
     let mut fs = vec!();
     let mut vs = vec!();
+    let mut cs = vec!();
     let mut gs = vec!();
     for g in uniq_globs.into_iter() {
+        let has_val = match g {
+            GVar(ref vi) => vi.borrow().val.is_some(),
+            _ => false
+        };
         match g {
             GOther => {}
             GFunc(_) => fs.push(g),
+            GVar(_) if has_val => cs.push(g),
             GVar(_) => vs.push(g),
             _ => gs.push(g)
         }
@@ -163,50 +283,67 @@ pub fn gen_mod(links: &[(String, LinkType)], globs: Vec<Global>, span: Span) ->
         match g {
             GType(ti) => {
                 let t = ti.borrow().clone();
-                defs.extend(ctypedef_to_rs(&mut ctx, t.name.clone(), &t.ty).into_iter())
+                defs.extend(ctypedef_to_rs(ctx, t.name.clone(), &t.comment[..], &t.ty).into_iter())
             },
             GCompDecl(ci) => {
                 {
                     let mut c = ci.borrow_mut();
-                    c.name = unnamed_name(&mut ctx, c.name.clone());
+                    c.name = unnamed_name(ctx, c.name.clone());
                 }
                 let c = ci.borrow().clone();
-                defs.push(opaque_to_rs(&mut ctx, comp_name(c.kind, &c.name)));
+                defs.push(opaque_to_rs(ctx, comp_name(c.kind, &c.name)));
             },
             GComp(ci) => {
                 {
                     let mut c = ci.borrow_mut();
-                    c.name = unnamed_name(&mut ctx, c.name.clone());
+                    c.name = unnamed_name(ctx, c.name.clone());
                 }
                 let c = ci.borrow().clone();
-                defs.extend(comp_to_rs(&mut ctx, c.kind, comp_name(c.kind, &c.name),
-                                       c.layout, c.members).into_iter())
+                defs.extend(comp_to_rs(ctx, comp_name(c.kind, &c.name), c).into_iter())
             },
             GEnumDecl(ei) => {
                 {
                     let mut e = ei.borrow_mut();
-                    e.name = unnamed_name(&mut ctx, e.name.clone());
+                    e.name = unnamed_name(ctx, e.name.clone());
                 }
                 let e = ei.borrow().clone();
-                defs.push(opaque_to_rs(&mut ctx, enum_name(&e.name)));
+                defs.push(opaque_to_rs(ctx, enum_name(&e.name)));
             },
             GEnum(ei) => {
                 {
                     let mut e = ei.borrow_mut();
-                    e.name = unnamed_name(&mut ctx, e.name.clone());
+                    e.name = unnamed_name(ctx, e.name.clone());
                 }
                 let e = ei.borrow().clone();
-                defs.extend(cenum_to_rs(&mut ctx, enum_name(&e.name), e.kind, e.items).into_iter())
+                defs.extend(cenum_to_rs(ctx, enum_name(&e.name), e).into_iter())
+            },
+            GObjC(oi) => {
+                let o = oi.borrow().clone();
+                defs.extend(cobjc_to_rs(ctx, o).into_iter())
             },
             _ => { }
         }
     }
 
+    for c in cs.into_iter() {
+        match c {
+            GVar(vi) => {
+                let v = vi.borrow();
+                if let Some(ref val) = v.val {
+                    let attrs = mk_var_attrs(ctx, &v);
+                    defs.push(cconst_to_rs(ctx, v.name.clone(), attrs, &v.ty, val));
+                }
+            },
+            _ => unreachable!()
+        }
+    }
+
     let vars = vs.into_iter().map(|v| {
         match v {
             GVar(vi) => {
                 let v = vi.borrow();
-                cvar_to_rs(&mut ctx, v.name.clone(), &v.ty, v.is_const)
+                let attrs = mk_var_attrs(ctx, &v);
+                cvar_to_rs(ctx, v.name.clone(), v.mangled.clone(), attrs, &v.ty, v.is_const)
             },
             _ => unreachable!()
         }
@@ -219,9 +356,8 @@ pub fn gen_mod(links: &[(String, LinkType)], globs: Vec<Global>, span: Span) ->
                     let v = vi.borrow();
                     match v.ty {
                         TFuncPtr(ref sig) => {
-                            let decl = cfunc_to_rs(&mut ctx, v.name.clone(),
-                                                   &*sig.ret_ty, &sig.args[..],
-                                                   sig.is_variadic);
+                            let attrs = mk_var_attrs(ctx, &v);
+                            let decl = cfunc_to_rs(ctx, v.name.clone(), v.mangled.clone(), attrs, sig);
                             (sig.abi, decl)
                         }
                         _ => unreachable!()
@@ -246,15 +382,13 @@ pub fn gen_mod(links: &[(String, LinkType)], globs: Vec<Global>, span: Span) ->
     };
 
     if !Vec::is_empty(&vars) {
-        defs.push(mk_extern(&mut ctx, links, vars, abi::C));
+        defs.push(mk_extern(ctx, &options.links[..], vars, abi::C));
     }
 
     for (abi, funcs) in funcs.into_iter() {
-        defs.push(mk_extern(&mut ctx, links, funcs, abi));
+        defs.push(mk_extern(ctx, &options.links[..], funcs, abi));
     }
 
-    //let attrs = vec!(mk_attr_list(&mut ctx, "allow", ["dead_code", "non_camel_case_types", "uppercase_variables"]));
-
     defs
 }
 
@@ -417,8 +551,8 @@ fn tag_dup_decl(gs: Vec<Global>) -> Vec<Global> {
     return res;
 }
 
-fn ctypedef_to_rs(ctx: &mut GenCtx, name: String, ty: &Type) -> Vec<P<ast::Item>> {
-    fn mk_item(ctx: &mut GenCtx, name: String, ty: &Type) -> P<ast::Item> {
+fn ctypedef_to_rs(ctx: &mut GenCtx, name: String, comment: &str, ty: &Type) -> Vec<P<ast::Item>> {
+    fn mk_item(ctx: &mut GenCtx, name: String, comment: &str, ty: &Type) -> P<ast::Item> {
         let rust_name = rust_type_id(ctx, name);
         let rust_ty = cty_to_rs(ctx, ty);
         let base = ast::ItemTy(
@@ -432,7 +566,7 @@ fn ctypedef_to_rs(ctx: &mut GenCtx, name: String, ty: &Type) -> Vec<P<ast::Item>
 
         return P(ast::Item {
                   ident: ctx.ext_cx.ident_of(&rust_name[..]),
-                  attrs: Vec::new(),
+                  attrs: mk_doc_attrs(ctx, comment),
                   id: ast::DUMMY_NODE_ID,
                   node: base,
                   vis: ast::Public,
@@ -445,35 +579,423 @@ fn ctypedef_to_rs(ctx: &mut GenCtx, name: String, ty: &Type) -> Vec<P<ast::Item>
             let is_empty = ci.borrow().name.is_empty();
             if is_empty {
                 ci.borrow_mut().name = name.clone();
-                let c = ci.borrow().clone();
-                comp_to_rs(ctx, c.kind, name, c.layout, c.members)
+                let mut c = ci.borrow().clone();
+                if c.comment.is_empty() {
+                    c.comment = comment.to_string();
+                }
+                comp_to_rs(ctx, name, c)
             } else {
-                vec!(mk_item(ctx, name, ty))
+                vec!(mk_item(ctx, name, comment, ty))
             }
         },
         TEnum(ref ei) => {
             let is_empty = ei.borrow().name.is_empty();
             if is_empty {
                 ei.borrow_mut().name = name.clone();
-                let e = ei.borrow().clone();
-                cenum_to_rs(ctx, name, e.kind, e.items)
+                let mut e = ei.borrow().clone();
+                if e.comment.is_empty() {
+                    e.comment = comment.to_string();
+                }
+                if enum_style(ctx, &name[..]) == EnumStyle::Module {
+                    // The module cannot double as the type, so it gets the
+                    // name of a tagged enum and the typedef refers to it.
+                    let mut items = cenum_to_rs(ctx, enum_name(&name), e);
+                    items.push(mk_item(ctx, name, comment, ty));
+                    items
+                } else {
+                    cenum_to_rs(ctx, name, e)
+                }
             } else {
-                vec!(mk_item(ctx, name, ty))
+                vec!(mk_item(ctx, name, comment, ty))
             }
         },
-        _ => vec!(mk_item(ctx, name, ty))
+        _ => vec!(mk_item(ctx, name, comment, ty))
     }
 }
 
-fn comp_to_rs(ctx: &mut GenCtx, kind: CompKind, name: String,
-              layout: Layout, members: Vec<CompMember>) -> Vec<P<ast::Item>> {
-    match kind {
-        CompKind::Struct => cstruct_to_rs(ctx, name, members),
-        CompKind::Union =>  cunion_to_rs(ctx, name, layout, members),
+fn comp_to_rs(ctx: &mut GenCtx, name: String, ci: CompInfo) -> Vec<P<ast::Item>> {
+    let class = ci.name.clone();
+    let methods = ci.methods.clone();
+    // The vtable of an opaque class is hidden in its blob.
+    let vmethods = if ci.opaque { vec!() } else { ci.vmethods.clone() };
+    let base = ci.base.clone();
+    let mut items = match ci.kind {
+        _ if ci.opaque => cblob_to_rs(ctx, name.clone(), ci),
+        CompKind::Struct => cstruct_to_rs(ctx, name.clone(), ci),
+        CompKind::Union =>  cunion_to_rs(ctx, name.clone(), ci),
+    };
+    if !vmethods.is_empty() {
+        items.extend(cxx_vtable_to_rs(ctx, &name[..], &class[..], &base, &vmethods[..]).into_iter());
+    }
+    if !methods.is_empty() || !vmethods.is_empty() {
+        items.extend(cxx_methods_to_rs(ctx, &name[..], &class[..], &methods[..],
+                                       &vmethods[..]).into_iter());
+    }
+    items
+}
+
+fn unique_name(names: &mut Vec<String>, name: String) -> String {
+    let mut unique = name.clone();
+    let mut n = 0;
+    while names.contains(&unique) {
+        n += 1;
+        unique = format!("{}{}", name, n);
+    }
+    names.push(unique.clone());
+    unique
+}
+
+/// The vtable slots added by the virtual methods of a class, as field names
+/// and methods. A virtual destructor takes two slots in the Itanium ABI, the
+/// complete destructor and the deleting one, which only the former is called
+/// through.
+fn vtable_slots(ctx: &mut GenCtx, vmethods: &[MethodInfo]) -> Vec<(String, Option<MethodInfo>)> {
+    let mut names = vec!();
+    let mut slots = vec!();
+    for m in vmethods.iter() {
+        if m.kind == MethodKind::Destructor {
+            slots.push((unique_name(&mut names, "destruct".to_string()), Some(m.clone())));
+            slots.push((unique_name(&mut names, "destruct_deleting".to_string()), None));
+        } else {
+            let name = rust_type_id(ctx, m.name.clone());
+            slots.push((unique_name(&mut names, name), Some(m.clone())));
+        }
+    }
+    slots
+}
+
+/// Generates the vtable of a C++ class as a struct of function pointers
+/// taking `this` first, starting with the vtable of its base if it has one.
+fn cxx_vtable_to_rs(ctx: &mut GenCtx, ty_name: &str, class: &str,
+                    base: &Option<Rc<RefCell<CompInfo>>>,
+                    vmethods: &[MethodInfo]) -> Vec<P<ast::Item>> {
+    let mut members = vec!();
+    if let Some(ref base) = *base {
+        let base = base.borrow();
+        if base.has_vtable {
+            let base_vtable = format!("{}_Vtable", comp_name(base.kind, &base.name));
+            let mut base_ti = TypeInfo::new(base_vtable, TVoid);
+            base_ti.namespace = base.namespace.clone();
+            let base_ty = TNamed(Rc::new(RefCell::new(base_ti)));
+            members.push(CompMember::Field(FieldInfo::new("_base".to_string(), base_ty, None)));
+        }
+    }
+
+    let mut class_ti = TypeInfo::new(ty_name.to_string(), TVoid);
+    class_ti.namespace = ctx.module.clone();
+    let class_ty = TNamed(Rc::new(RefCell::new(class_ti)));
+    for (name, m) in vtable_slots(ctx, vmethods).into_iter() {
+        let m = match m {
+            Some(m) => m,
+            None => vmethods.iter().find(|m| m.kind == MethodKind::Destructor).unwrap().clone()
+        };
+        let mut sig = m.sig.clone();
+        let this_ty = TPtr(Box::new(class_ty.clone()), m.is_const, Layout::zero());
+        sig.args.insert(0, ("this".to_string(), this_ty));
+        members.push(CompMember::Field(FieldInfo::new(name, TFuncPtr(sig), None)));
+    }
+
+    let mut vtable = CompInfo::new(class.to_string(), CompKind::Struct, members, Layout::zero());
+    vtable.comment = format!("The virtual methods of `{}`, in vtable order.", class);
+    cstruct_to_rs(ctx, format!("{}_Vtable", ty_name), vtable)
+}
+
+/// Generates the methods of a C++ class: the mangled functions of the
+/// non-virtual ones, named `<class>_<method>`, and an impl of methods
+/// calling them or dispatching through the vtable for public virtual ones.
+/// Constructors become `new` and the destructor `destruct`.
+///
+/// `new` constructs the object in storage given by the caller rather than
+/// returning it, since moving a C++ object breaks those that point to
+/// themselves or whose address was handed out by the constructor.
+fn cxx_methods_to_rs(ctx: &mut GenCtx, ty_name: &str, class: &str,
+                     methods: &[MethodInfo], vmethods: &[MethodInfo]) -> Vec<P<ast::Item>> {
+    let id = rust_type_id(ctx, ty_name.to_string());
+    let mut class_ti = TypeInfo::new(ty_name.to_string(), TVoid);
+    class_ti.namespace = ctx.module.clone();
+    let class_ty = TNamed(Rc::new(RefCell::new(class_ti)));
+
+    let mut names = vec!();
+    let mut funcs = vec!();
+    let mut wrappers = String::new();
+    let mut calls = vec!();
+    for m in methods.iter() {
+        // Rust has no way to forward variadic arguments
+        if m.sig.is_variadic {
+            continue;
+        }
+
+        let name = match m.kind {
+            MethodKind::Constructor => "new".to_string(),
+            MethodKind::Destructor => "destruct".to_string(),
+            _ => first(rust_id(ctx, m.name.clone()))
+        };
+        let name = unique_name(&mut names, name);
+        let func_name = format!("{}_{}", class, name);
+
+        let mut sig = m.sig.clone();
+        if m.kind != MethodKind::Static {
+            let this_ty = TPtr(Box::new(class_ty.clone()), m.is_const, Layout::zero());
+            sig.args.insert(0, ("this".to_string(), this_ty));
+        }
+        funcs.push(cfunc_to_rs(ctx, func_name.clone(), Some(m.mangled.clone()), vec!(), &sig));
+        calls.push((name, func_name, m.clone()));
+    }
+
+    let vtable = rust_type_id(ctx, format!("{}_Vtable", ty_name));
+    for (slot, m) in vtable_slots(ctx, vmethods).into_iter() {
+        let m = match m {
+            Some(ref m) if !m.sig.is_variadic && m.is_public => m.clone(),
+            _ => continue
+        };
+        let name = unique_name(&mut names, slot.clone());
+        let callee = format!("let vtable = *(self as *const Self as *const *const {}); \
+                              ((*vtable).{}.unwrap())", vtable, slot);
+        calls.push((name, callee, m));
+    }
+
+    for &(ref name, ref callee, ref m) in calls.iter() {
+        let decl = cfuncty_to_rs(ctx, &*m.sig.ret_ty, &m.sig.args[..], false, m.sig.noreturn);
+        let params: Vec<String> = decl.inputs.iter().map(|a| {
+            format!("{}: {}", pat_to_string(&a.pat), ty_to_string(&a.ty))
+        }).collect();
+        let call_args: Vec<String> = decl.inputs.iter().map(|a| pat_to_string(&a.pat)).collect();
+        let ret = match (&*m.sig.ret_ty, &decl.output) {
+            (_, &ast::NoReturn(_)) => " -> !".to_string(),
+            (&TVoid, _) => String::new(),
+            (_, &ast::Return(ref ty)) => format!(" -> {}", ty_to_string(ty)),
+            _ => String::new()
+        };
+
+        let (params, body) = match m.kind {
+            MethodKind::Instance => {
+                let this = if m.is_const { "&self" } else { "&mut self" };
+                let mut params_ = vec!(this.to_string());
+                params_.extend(params.into_iter());
+                let mut call_args_ = vec!("self".to_string());
+                call_args_.extend(call_args.into_iter());
+                (params_, format!("{}({})", callee, call_args_.connect(", ")))
+            }
+            MethodKind::Static => {
+                (params, format!("{}({})", callee, call_args.connect(", ")))
+            }
+            MethodKind::Constructor => {
+                let mut params_ = vec!("this: *mut Self".to_string());
+                params_.extend(params.into_iter());
+                let mut call_args_ = vec!("this".to_string());
+                call_args_.extend(call_args.into_iter());
+                (params_, format!("{}({})", callee, call_args_.connect(", ")))
+            }
+            MethodKind::Destructor => {
+                (vec!("&mut self".to_string()), format!("{}(self)", callee))
+            }
+        };
+        wrappers.push_str(&format!("
+            {}#[inline]
+            pub unsafe fn {}({}){} {{ {} }}",
+            doc_attrs_str(&m.comment[..]), name, params.connect(", "), ret, body)[..]);
+    }
+
+    let mut items = vec!();
+    if !funcs.is_empty() {
+        let options = ctx.options;
+        items.push(mk_extern(ctx, &options.links[..], funcs, abi::C));
+    }
+    if !wrappers.is_empty() {
+        items.extend(parse_items(ctx, format!("impl {} {{{}\n}}", id, wrappers)).into_iter());
+    }
+    items
+}
+
+/// Generates an Objective-C interface, category or protocol as a trait
+/// implemented for `id`, whose methods send the matching message with
+/// `msg_send!`.  Methods are named after their selector, with colons
+/// replaced by underscores.  Class methods of protocols have no class to
+/// be sent to and are left out.
+fn cobjc_to_rs(ctx: &mut GenCtx, oi: ObjCInfo) -> Vec<P<ast::Item>> {
+    let (name, class) = match oi.kind {
+        ObjCKind::Interface => (oi.name.clone(), Some(oi.name.clone())),
+        ObjCKind::Category => (format!("{}_{}", oi.class, oi.name), Some(oi.class.clone())),
+        ObjCKind::Protocol => (format!("Protocol_{}", oi.name), None),
+    };
+    let id = rust_type_id(ctx, name);
+    let objc_id = cty_to_rs(ctx, &TObjC(OId, Layout::zero()));
+
+    let mut names = vec!();
+    let mut decls = String::new();
+    let mut impls = String::new();
+    for m in oi.methods.iter() {
+        let receiver = match (m.is_class, &class) {
+            (false, _) => "self".to_string(),
+            (true, &Some(ref class)) => format!("::objc::runtime::Class::get({:?}).unwrap()", class),
+            (true, &None) => continue
+        };
+        let keywords: Vec<&str> = if m.sig.args.is_empty() {
+            vec!(&m.selector[..])
+        } else {
+            m.selector.trim_right_matches(':').split(':').collect()
+        };
+        // `msg_send!` can't send selectors with unnamed parts
+        if keywords.len() != cmp::max(m.sig.args.len(), 1) || keywords.iter().any(|k| k.is_empty()) {
+            continue;
+        }
+
+        let decl = cfuncty_to_rs(ctx, &*m.sig.ret_ty, &m.sig.args[..], false, false);
+        let mut params = if m.is_class { vec!() } else { vec!("self".to_string()) };
+        params.extend(decl.inputs.iter().map(|a| {
+            format!("{}: {}", pat_to_string(&a.pat), ty_to_string(&a.ty))
+        }));
+        let message = if decl.inputs.is_empty() {
+            m.selector.clone()
+        } else {
+            let parts: Vec<String> = keywords.iter().zip(decl.inputs.iter()).map(|(k, a)| {
+                format!("{}:{}", k, pat_to_string(&a.pat))
+            }).collect();
+            parts.connect(" ")
+        };
+        let ret = match (&*m.sig.ret_ty, &decl.output) {
+            (&TVoid, _) => String::new(),
+            (_, &ast::Return(ref ty)) => format!(" -> {}", ty_to_string(ty)),
+            _ => String::new()
+        };
+
+        let method = rust_type_id(ctx, m.selector.replace(":", "_"));
+        let method = unique_name(&mut names, method);
+        let sig = format!("unsafe fn {}({}){}", method, params.connect(", "), ret);
+        decls.push_str(&format!("\n{}{};", doc_attrs_str(&m.comment[..]), sig)[..]);
+        impls.push_str(&format!("\n{} {{ msg_send![{}, {}] }}", sig, receiver, message)[..]);
+    }
+
+    let src = format!("{}pub trait {} {{{}\n}}\nimpl {} for {} {{{}\n}}",
+                      doc_attrs_str(&oi.comment[..]), id, decls, id, ty_to_string(&objc_id), impls);
+    parse_items(ctx, src)
+}
+
+/// Generates a struct or union chosen to be opaque as a blob with the same
+/// size and alignment, so that it can still be used by value.
+fn cblob_to_rs(ctx: &mut GenCtx, name: String, ci: CompInfo) -> Vec<P<ast::Item>> {
+    let def = ast::ItemStruct(
+        P(ast::StructDef {
+           fields: vec!(mk_blob_field(ctx, "_bindgen_opaque_blob_", ci.layout)),
+           ctor_id: None,
+        }),
+        empty_generics()
+    );
+
+    let over_align = if ci.layout.align > blob_align(ci.layout) { Some(ci.layout.align) } else { None };
+    let id = rust_type_id(ctx, name.clone());
+    let mut attrs = mk_doc_attrs(ctx, &ci.comment[..]);
+    attrs.push(mk_repr_attr(ctx, None, over_align));
+    attrs.push(mk_deriving_copy_attr(ctx));
+    let def = P(ast::Item {
+        ident: ctx.ext_cx.ident_of(&id[..]),
+        attrs: attrs,
+        id: ast::DUMMY_NODE_ID,
+        node: def,
+        vis: ast::Public,
+        span: ctx.span
+    });
+
+    let mut items = vec!(def, mk_clone_impl(ctx, &name[..]), mk_default_impl(ctx, &name[..]));
+    if ctx.options.layout_tests && ci.layout.size > 0 {
+        items.push(mk_layout_test(ctx, &name[..], ci.layout, &[], false));
+    }
+    items
+}
+
+/// The offset of the base class subobject in a class with a single,
+/// non-virtual base. A dynamic class puts its vtable pointer first when the
+/// base doesn't have one to share.
+fn base_offset(ci: &CompInfo, base: &CompInfo) -> usize {
+    if ci.has_vtable && !base.has_vtable {
+        align_to(mem::size_of::<usize>(), base.layout.align)
+    } else {
+        0
+    }
+}
+
+/// Whether a field of a class starts before the end of its base class, in
+/// the tail padding of a non-POD base or over an empty base. The base can't
+/// be kept as a single field then, its members are laid out in the class.
+fn overlaps_base(ci: &CompInfo) -> bool {
+    let base = match ci.base {
+        Some(ref base) => base.borrow(),
+        None => return false
+    };
+    let base_end = base_offset(ci, &base) + base.layout.size;
+    ci.members.iter().any(|m| match m {
+        &CompMember::Field(ref f) | &CompMember::CompField(_, ref f) =>
+            f.offset.map_or(false, |bits| bits / 8 < base_end),
+        &CompMember::Comp(_) => false
+    })
+}
+
+/// Whether a class or one of its bases is bound as opaque.
+fn has_opaque_base(ci: &CompInfo) -> bool {
+    ci.opaque || ci.base.as_ref().map_or(false, |b| has_opaque_base(&b.borrow()))
+}
+
+/// Appends the data members of a base class, and of its own bases, to
+/// `members`, moved by the offset of the base in the derived class.
+fn base_members(base: &CompInfo, offset: usize, members: &mut Vec<CompMember>) {
+    if let Some(ref b) = base.base {
+        let b = b.borrow();
+        base_members(&b, offset + base_offset(base, &b), members);
+    }
+    for m in base.members.iter() {
+        match m {
+            &CompMember::Field(ref f) | &CompMember::CompField(_, ref f) => {
+                let mut f = f.clone();
+                f.offset = f.offset.map(|bits| bits + offset * 8);
+                members.push(CompMember::Field(f));
+            }
+            &CompMember::Comp(ref c) if c.borrow().name.is_empty() => members.push(m.clone()),
+            &CompMember::Comp(_) => ()
+        }
+    }
+}
+
+/// The vtable type of a dynamic class, its own when it adds virtual methods,
+/// else the one of the base it shares its vtable with.
+fn vtable_ty(name: String, namespace: Vec<String>, ci: &CompInfo) -> Type {
+    if let Some(ref base) = ci.base {
+        let base = base.borrow();
+        if base.has_vtable && ci.vmethods.is_empty() {
+            return vtable_ty(comp_name(base.kind, &base.name), base.namespace.clone(), &base);
+        }
     }
+    let mut vtable_ti = TypeInfo::new(format!("{}_Vtable", name), TVoid);
+    vtable_ti.namespace = namespace;
+    TNamed(Rc::new(RefCell::new(vtable_ti)))
 }
 
-fn cstruct_to_rs(ctx: &mut GenCtx, name: String, members: Vec<CompMember>) -> Vec<P<ast::Item>> {
+fn cstruct_to_rs(ctx: &mut GenCtx, name: String, ci: CompInfo) -> Vec<P<ast::Item>> {
+    // A dynamic C++ class starts with its vtable pointer, unless it shares
+    // the one of its base, followed by the base class subobject. A base whose
+    // tail padding holds fields of the class has its members inlined.
+    let inline_base = overlaps_base(&ci);
+    if inline_base && ci.base.as_ref().map_or(false, |b| has_opaque_base(&b.borrow())) {
+        let msg = format!("fields of `{}` overlap its opaque base class, it is bound as opaque", name);
+        ctx.logger.log(&Diagnostic::new(Severity::Warning, Category::Layout, msg));
+        return cblob_to_rs(ctx, name, ci);
+    }
+    let mut members = vec!();
+    let base_has_vtable = ci.base.as_ref().map_or(false, |b| b.borrow().has_vtable);
+    if ci.has_vtable && (!base_has_vtable || inline_base) {
+        let ptr_layout = Layout::new(mem::size_of::<usize>(), mem::align_of::<usize>());
+        let vtable = vtable_ty(name.clone(), ctx.module.clone(), &ci);
+        let vtable_ty = TPtr(Box::new(vtable), true, ptr_layout);
+        members.push(CompMember::Field(FieldInfo::new("_vtable".to_string(), vtable_ty, None)));
+    }
+    if let Some(ref base) = ci.base {
+        if inline_base {
+            let base = base.borrow();
+            base_members(&base, base_offset(&ci, &base), &mut members);
+        } else {
+            members.push(CompMember::Field(FieldInfo::new("_base".to_string(), TComp(base.clone()), None)));
+        }
+    }
+    members.extend(ci.members.into_iter());
     let mut fields = vec!();
     let mut methods = vec!();
     // Nested composites may need to emit declarations and implementations as
@@ -482,6 +1004,32 @@ fn cstruct_to_rs(ctx: &mut GenCtx, name: String, members: Vec<CompMember>) -> Ve
     let mut extra = vec!();
     let mut unnamed: u32 = 0;
     let mut bitfields: u32 = 0;
+    let mut padding: u32 = 0;
+    // The end of the last field and the alignment of the struct as rustc
+    // would lay them out, to check against the layout reported by clang.
+    let mut end = 0;
+    let mut align = 1;
+    // Names and C offsets of the fields, for the layout test.
+    let mut offsets = vec!();
+
+    // A record aligned less than its members is packed, the packing then
+    // also caps the alignment of every field.
+    let natural_align = members.iter().map(|m| match m {
+        &CompMember::Field(ref f) | &CompMember::CompField(_, ref f) => f.ty.align(),
+        &CompMember::Comp(ref rc_c) => {
+            let c = rc_c.borrow();
+            if c.name.is_empty() { c.layout.align } else { 1 }
+        }
+    }).fold(1, cmp::max);
+    let pack = if ci.layout.align > 0 && ci.layout.align < natural_align {
+        Some(ci.layout.align)
+    } else {
+        None
+    };
+    let packed_align = |align: usize| match pack {
+        Some(pack) => cmp::min(align, pack),
+        None => align
+    };
 
     for m in members.iter() {
         let (opt_rc_c, opt_f) = match m {
@@ -499,6 +1047,49 @@ fn cstruct_to_rs(ctx: &mut GenCtx, name: String, members: Vec<CompMember>) -> Ve
                 None => rust_type_id(ctx, f.name.clone())
             };
 
+            // Bitfields the storage unit can't be placed for are kept as
+            // bytes, so that the fields after them keep their offsets.
+            let unit = match f.bitfields {
+                Some(_) => match bitfield_unit(f, end, pack) {
+                    Some(unit) => Some(unit),
+                    None => {
+                        let (first, last) = bitfield_bytes(f);
+                        let start = cmp::max(first, end);
+                        let names: Vec<String> = f.bitfields.iter().flat_map(|b| b.iter())
+                            .filter(|&&(ref n, _, _)| !n.is_empty())
+                            .map(|&(ref n, _, _)| format!("`{}`", n)).collect();
+                        if !names.is_empty() {
+                            let msg = format!("bitfields {} of `{}` can't be stored at their offset \
+                                               in C, they have no accessors", names.connect(", "), name);
+                            ctx.logger.log(&Diagnostic::new(Severity::Warning, Category::Layout, msg));
+                        }
+                        if last > start {
+                            let layout = Layout::new(last - start, 1);
+                            end = pad_to_offset(ctx, &name[..], &f_name[..], &mut fields, &mut padding,
+                                                end, Some(start), 1) + layout.size;
+                            offsets.push((f_name.clone(), start));
+                            fields.push(mk_blob_field(ctx, &f_name[..], layout));
+                        }
+                        continue;
+                    }
+                },
+                None => None
+            };
+            let decl_ty = f.ty.clone();
+            let f = match unit {
+                Some(ref unit) => unit,
+                None => f
+            };
+
+            let offset = f.offset.map(|bits| bits / 8);
+            let f_align = packed_align(f.ty.align());
+            end = pad_to_offset(ctx, &name[..], &f_name[..], &mut fields, &mut padding,
+                                end, offset, f_align) + f.ty.size();
+            align = cmp::max(align, f_align);
+            if let Some(offset) = offset {
+                offsets.push((f_name.clone(), offset));
+            }
+
             let f_ty = P(cty_to_rs(ctx, &f.ty));
 
             fields.push(respan(ctx.span, ast::StructField_ {
@@ -508,8 +1099,14 @@ fn cstruct_to_rs(ctx: &mut GenCtx, name: String, members: Vec<CompMember>) -> Ve
                 ),
                 id: ast::DUMMY_NODE_ID,
                 ty: f_ty,
-                attrs: Vec::new()
+                attrs: mk_doc_attrs(ctx, &f.comment[..])
             }));
+
+            if f.bitfields.is_some() {
+                let ctor_name = format!("new_bitfield_{}", bitfields);
+                methods.extend(gen_bitfield_methods(ctx, BitfieldUnit::Field(&f_name[..]),
+                                                    f, &decl_ty, Some(&ctor_name[..])).into_iter());
+            }
         }
 
         if let Some(rc_c) = opt_rc_c {
@@ -517,15 +1114,41 @@ fn cstruct_to_rs(ctx: &mut GenCtx, name: String, members: Vec<CompMember>) -> Ve
             if c.name.is_empty() {
                 unnamed += 1;
                 let field_name = format!("_bindgen_data_{}_", unnamed);
+                let c_align = packed_align(blob_align(c.layout));
+                end = pad_to_offset(ctx, &name[..], &field_name[..], &mut fields, &mut padding,
+                                    end, None, c_align) + c.layout.size;
+                align = cmp::max(align, c_align);
                 fields.push(mk_blob_field(ctx, &field_name[..], c.layout));
                 methods.extend(gen_comp_methods(ctx, &field_name[..], 0, c.kind, &c.members, &mut extra).into_iter());
             } else {
-                extra.extend(comp_to_rs(ctx, c.kind, comp_name(c.kind, &c.name),
-                                        c.layout, c.members.clone()).into_iter());
+                extra.extend(comp_to_rs(ctx, comp_name(c.kind, &c.name), c.clone()).into_iter());
             }
         }
     }
 
+    let over_align = if pack.is_none() && ci.layout.align > align {
+        align = ci.layout.align;
+        Some(ci.layout.align)
+    } else {
+        None
+    };
+
+    if ci.layout.size > 0 {
+        let mut size = align_to(end, align);
+        if size < ci.layout.size {
+            padding += 1;
+            let field_name = format!("_bindgen_padding_{}_", padding);
+            fields.push(mk_blob_field(ctx, &field_name[..], Layout::new(ci.layout.size - end, 1)));
+            size = align_to(ci.layout.size, align);
+        }
+        if size != ci.layout.size || align != ci.layout.align {
+            let msg = format!("layout of `{}` diverges from C: size {} and alignment {} \
+                               in Rust, size {} and alignment {} in C",
+                              name, size, align, ci.layout.size, ci.layout.align);
+            ctx.logger.log(&Diagnostic::new(Severity::Warning, Category::Layout, msg));
+        }
+    }
+
     let ctor_id = if fields.is_empty() { Some(ast::DUMMY_NODE_ID) } else { None };
     let def = ast::ItemStruct(
         P(ast::StructDef {
@@ -536,8 +1159,11 @@ fn cstruct_to_rs(ctx: &mut GenCtx, name: String, members: Vec<CompMember>) -> Ve
     );
 
     let id = rust_type_id(ctx, name.clone());
+    let mut attrs = mk_doc_attrs(ctx, &ci.comment[..]);
+    attrs.push(mk_repr_attr(ctx, pack, over_align));
+    attrs.push(mk_deriving_copy_attr(ctx));
     let struct_def = P(ast::Item { ident: ctx.ext_cx.ident_of(&id[..]),
-        attrs: vec!(mk_repr_attr(ctx), mk_deriving_copy_attr(ctx)),
+        attrs: attrs,
         id: ast::DUMMY_NODE_ID,
         node: def,
         vis: ast::Public,
@@ -566,6 +1192,9 @@ fn cstruct_to_rs(ctx: &mut GenCtx, name: String, members: Vec<CompMember>) -> Ve
 
     items.push(mk_clone_impl(ctx, &name[..]));
     items.push(mk_default_impl(ctx, &name[..]));
+    if ctx.options.layout_tests && ci.layout.size > 0 {
+        items.push(mk_layout_test(ctx, &name[..], ci.layout, &offsets[..], false));
+    }
     items.extend(extra.into_iter());
     items
 }
@@ -588,7 +1217,7 @@ fn opaque_to_rs(ctx: &mut GenCtx, name: String) -> P<ast::Item> {
            });
 }
 
-fn cunion_to_rs(ctx: &mut GenCtx, name: String, layout: Layout, members: Vec<CompMember>) -> Vec<P<ast::Item>> {
+fn cunion_to_rs(ctx: &mut GenCtx, name: String, ci: CompInfo) -> Vec<P<ast::Item>> {
     fn mk_item(ctx: &mut GenCtx, name: String, item: ast::Item_, vis:
                ast::Visibility, attrs: Vec<ast::Attribute>) -> P<ast::Item> {
         return P(ast::Item {
@@ -601,8 +1230,13 @@ fn cunion_to_rs(ctx: &mut GenCtx, name: String, layout: Layout, members: Vec<Com
         });
     }
 
-    let ci = Rc::new(RefCell::new(CompInfo::new(name.clone(), CompKind::Union, members.clone(), layout)));
-    let union = TNamed(Rc::new(RefCell::new(TypeInfo::new(name.clone(), TComp(ci)))));
+    let layout = ci.layout;
+    let members = ci.members.clone();
+    let comment = ci.comment.clone();
+    let ci = Rc::new(RefCell::new(ci));
+    let mut union_ti = TypeInfo::new(name.clone(), TComp(ci));
+    union_ti.namespace = ctx.module.clone();
+    let union = TNamed(Rc::new(RefCell::new(union_ti)));
 
     // Nested composites may need to emit declarations and implementations as
     // they are encountered.  The declarations end up in 'extra' and are emitted
@@ -612,6 +1246,17 @@ fn cunion_to_rs(ctx: &mut GenCtx, name: String, layout: Layout, members: Vec<Com
     let data_field_name = "_bindgen_data_";
     let data_field = mk_blob_field(ctx, data_field_name, layout);
 
+    let natural_align = members.iter().map(|m| match m {
+        &CompMember::Field(ref f) | &CompMember::CompField(_, ref f) => f.ty.align(),
+        &CompMember::Comp(ref rc_c) => rc_c.borrow().layout.align,
+    }).fold(1, cmp::max);
+    let pack = if layout.align > 0 && layout.align < natural_align {
+        Some(layout.align)
+    } else {
+        None
+    };
+    let over_align = if layout.align > blob_align(layout) { Some(layout.align) } else { None };
+
     let def = ast::ItemStruct(
         P(ast::StructDef {
            fields: vec!(data_field),
@@ -620,7 +1265,9 @@ fn cunion_to_rs(ctx: &mut GenCtx, name: String, layout: Layout, members: Vec<Com
         empty_generics()
     );
     let union_id = rust_type_id(ctx, name.clone());
-    let union_attrs = vec!(mk_repr_attr(ctx), mk_deriving_copy_attr(ctx));
+    let mut union_attrs = mk_doc_attrs(ctx, &comment[..]);
+    union_attrs.push(mk_repr_attr(ctx, pack, over_align));
+    union_attrs.push(mk_deriving_copy_attr(ctx));
     let union_def = mk_item(ctx, union_id, def, ast::Public, union_attrs);
 
     let union_impl = ast::ItemImpl(
@@ -639,21 +1286,191 @@ fn cunion_to_rs(ctx: &mut GenCtx, name: String, layout: Layout, members: Vec<Com
 
     items.push(mk_clone_impl(ctx, &name[..]));
     items.push(mk_default_impl(ctx, &name[..]));
+    if ctx.options.layout_tests && layout.size > 0 {
+        // The named members of the union, through the accessors generated
+        // for them above.
+        let offsets: Vec<(String, usize)> = members.iter().filter_map(|m| match m {
+            &CompMember::Field(ref f) | &CompMember::CompField(_, ref f)
+                if f.bitfields.is_none() && !f.name.is_empty() => {
+                Some((rust_id(ctx, f.name.clone()).0, f.offset.map_or(0, |bits| bits / 8)))
+            }
+            _ => None
+        }).collect();
+        items.push(mk_layout_test(ctx, &name[..], layout, &offsets[..], true));
+    }
     items.extend(extra.into_iter());
     items
 }
 
-fn cenum_to_rs(ctx: &mut GenCtx, name: String, kind: IKind, items: Vec<EnumItem>) -> Vec<P<ast::Item>> {
-    let ty = TInt(kind, Layout::zero());
+/// The style in which the enum named `name` is generated.
+fn enum_style(ctx: &mut GenCtx, name: &str) -> EnumStyle {
+    for &mut (ref mut filter, style) in ctx.enum_styles.iter_mut() {
+        if filter.allowlists(name) {
+            return style;
+        }
+    }
+    ctx.options.enum_style
+}
+
+fn cenum_to_rs(ctx: &mut GenCtx, name: String, e: EnumInfo) -> Vec<P<ast::Item>> {
+    let style = enum_style(ctx, &e.name[..]);
+    match style {
+        EnumStyle::Consts => cenum_to_consts(ctx, name, e),
+        EnumStyle::Rust => cenum_to_rust_enum(ctx, name, e),
+        EnumStyle::Module => cenum_to_mod(ctx, name, e),
+        EnumStyle::NewType => cenum_to_newtype(ctx, name, e),
+        EnumStyle::Bitflags => cenum_to_bitflags(ctx, name, e),
+    }
+}
+
+/// Renders a comment as `#[doc]` attributes, for items generated from source.
+fn doc_attrs_str(comment: &str) -> String {
+    let mut attrs = String::new();
+    for line in comment.lines() {
+        attrs.push_str(&format!("#[doc = {:?}]\n", line)[..]);
+    }
+    attrs
+}
+
+fn parse_items(ctx: &GenCtx, src: String) -> Vec<P<ast::Item>> {
+    let mut parser = parse::new_parser_from_source_str(ctx.ext_cx.parse_sess(),
+        ctx.ext_cx.cfg(), "".to_string(), src);
+    let mut items = vec!();
+    while let Some(item) = parser.parse_item() {
+        items.push(item);
+    }
+    items
+}
+
+/// The primitive integer type of the same size and signedness as the enum.
+fn enum_repr(e: &EnumInfo) -> &'static str {
+    match (e.kind.is_signed(), e.layout.size) {
+        (true, 1) => "i8",
+        (false, 1) => "u8",
+        (true, 2) => "i16",
+        (false, 2) => "u16",
+        (true, 8) => "i64",
+        (false, 8) => "u64",
+        (true, _) => "i32",
+        (false, _) => "u32",
+    }
+}
+
+fn cenum_to_rust_enum(ctx: &mut GenCtx, name: String, e: EnumInfo) -> Vec<P<ast::Item>> {
+    let id = rust_type_id(ctx, name);
+    let mut variants = String::new();
+    let mut dups = String::new();
+    // Rust enums cannot repeat a discriminant, later enumerators with the
+    // value of an earlier one alias its variant instead.
+    let mut seen: HashMap<EnumVal, String> = HashMap::new();
+    for it in e.items.iter() {
+        let item_id = first(rust_id(ctx, it.name.clone()));
+        let doc = doc_attrs_str(&it.comment[..]);
+        match seen.entry(it.val) {
+            Entry::Occupied(occ) => {
+                dups.push_str(&format!("{}pub const {}: {} = {}::{};\n",
+                                       doc, item_id, id, id, occ.get())[..]);
+            }
+            Entry::Vacant(vac) => {
+                variants.push_str(&format!("{}{} = {},\n", doc, item_id, it.val)[..]);
+                vac.insert(item_id);
+            }
+        }
+    }
+
+    let mut src = format!("
+        {}#[repr({})]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum {} {{ {} }}
+    ", doc_attrs_str(&e.comment[..]), enum_repr(&e), id, variants);
+    if !dups.is_empty() {
+        src.push_str(&format!("impl {} {{ {} }}", id, dups)[..]);
+    }
+    parse_items(ctx, src)
+}
+
+fn cenum_to_mod(ctx: &mut GenCtx, name: String, e: EnumInfo) -> Vec<P<ast::Item>> {
+    let id = rust_type_id(ctx, name);
+    let ty = tts_to_string(&P(cty_to_rs(ctx, &TInt(e.kind, e.layout))).to_tokens(&ctx.ext_cx)[..]);
+    let mut consts = String::new();
+    for it in e.items.iter() {
+        let item_id = first(rust_id(ctx, it.name.clone()));
+        consts.push_str(&format!("{}pub const {}: Type = {};\n",
+                                 doc_attrs_str(&it.comment[..]), item_id, it.val)[..]);
+    }
+
+    let src = format!("
+        {}pub mod {} {{
+            pub type Type = {};
+            {}
+        }}
+    ", doc_attrs_str(&e.comment[..]), id, ty, consts);
+    parse_items(ctx, src)
+}
+
+fn cenum_to_newtype(ctx: &mut GenCtx, name: String, e: EnumInfo) -> Vec<P<ast::Item>> {
+    let id = rust_type_id(ctx, name);
+    let ty = tts_to_string(&P(cty_to_rs(ctx, &TInt(e.kind, e.layout))).to_tokens(&ctx.ext_cx)[..]);
+    let mut consts = String::new();
+    for it in e.items.iter() {
+        let item_id = first(rust_id(ctx, it.name.clone()));
+        consts.push_str(&format!("{}pub const {}: {} = {}({});\n",
+                                 doc_attrs_str(&it.comment[..]), item_id, id, id, it.val)[..]);
+    }
+
+    let src = format!("
+        {}#[repr(transparent)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct {}(pub {});
+        impl {} {{ {} }}
+    ", doc_attrs_str(&e.comment[..]), id, ty, id, consts);
+    parse_items(ctx, src)
+}
+
+fn cenum_to_bitflags(ctx: &mut GenCtx, name: String, e: EnumInfo) -> Vec<P<ast::Item>> {
+    let id = rust_type_id(ctx, name.clone());
+    let mut items = cenum_to_newtype(ctx, name, e);
+
+    let mut src = String::new();
+    for &(op, method, sym) in [("BitOr", "bitor", "|"),
+                               ("BitAnd", "bitand", "&"),
+                               ("BitXor", "bitxor", "^")].iter() {
+        src.push_str(&format!("
+            impl ::std::ops::{op} for {id} {{
+                type Output = {id};
+                #[inline]
+                fn {method}(self, other: {id}) -> {id} {{ {id}(self.0 {sym} other.0) }}
+            }}
+            impl ::std::ops::{op}Assign for {id} {{
+                #[inline]
+                fn {method}_assign(&mut self, other: {id}) {{ self.0 {sym}= other.0; }}
+            }}
+        ", op = op, method = method, sym = sym, id = id)[..]);
+    }
+    src.push_str(&format!("
+        impl ::std::ops::Not for {id} {{
+            type Output = {id};
+            #[inline]
+            fn not(self) -> {id} {{ {id}(!self.0) }}
+        }}
+    ", id = id)[..]);
+
+    items.extend(parse_items(ctx, src).into_iter());
+    items
+}
+
+fn cenum_to_consts(ctx: &mut GenCtx, name: String, e: EnumInfo) -> Vec<P<ast::Item>> {
+    let items = e.items;
+    let ty = TInt(e.kind, Layout::zero());
     let ty_id = rust_type_id(ctx, name);
-    let ty_def = ctypedef_to_rs(ctx, ty_id, &ty);
+    let ty_def = ctypedef_to_rs(ctx, ty_id, &e.comment[..], &ty);
     let val_ty = cty_to_rs(ctx, &ty);
     let mut def = ty_def;
 
     for it in items.iter() {
         let int_lit = ast::LitInt(
-            it.val.abs() as u64,
-            ast::UnsuffixedIntLit(if it.val < 0 { ast::Minus } else { ast::Plus })
+            it.val.magnitude(),
+            ast::UnsuffixedIntLit(if it.val.is_negative() { ast::Minus } else { ast::Plus })
         );
 
         let cst = ast::ItemConst(
@@ -664,7 +1481,7 @@ fn cenum_to_rs(ctx: &mut GenCtx, name: String, kind: IKind, items: Vec<EnumItem>
         let id = first(rust_id(ctx, it.name.clone()));
         let val_def = P(ast::Item {
                          ident: ctx.ext_cx.ident_of(&id[..]),
-                         attrs: Vec::new(),
+                         attrs: mk_doc_attrs(ctx, &it.comment[..]),
                          id: ast::DUMMY_NODE_ID,
                          node: cst,
                          vis: ast::Public,
@@ -686,9 +1503,6 @@ fn gen_comp_methods(ctx: &mut GenCtx, data_field: &str, data_offset: usize,
                     extra: &mut Vec<P<ast::Item>>) -> Vec<P<ast::ImplItem>> {
 
     let mk_field_method = |ctx: &mut GenCtx, f: &FieldInfo, offset: usize| {
-        // TODO: Implement bitfield accessors
-        if f.bitfields.is_some() { return None; }
-
         let (f_name, _) = rust_id(ctx, f.name.clone());
         let ret_ty = P(cty_to_rs(ctx, &TPtr(Box::new(f.ty.clone()), false, Layout::zero())));
 
@@ -717,37 +1531,367 @@ fn gen_comp_methods(ctx: &mut GenCtx, data_field: &str, data_offset: usize,
         })
     };
 
-    let mut offset = data_offset;
+    let mut end = 0;
     let mut methods = vec!();
     for m in members.iter() {
-        let advance_by = match m {
+        // Prefer the offset reported by clang, members of unions all start
+        // at the beginning of the data.
+        let (clang_offset, align, size) = match m {
+            &CompMember::Field(ref f) | &CompMember::CompField(_, ref f) => {
+                (f.offset.map(|bits| bits / 8), f.ty.align(), f.ty.size())
+            }
+            &CompMember::Comp(ref rc_c) => {
+                let c = rc_c.borrow();
+                (None, c.layout.align, c.layout.size)
+            }
+        };
+        let member_offset = match (clang_offset, kind) {
+            (Some(offset), _) => offset,
+            (None, CompKind::Struct) => align_to(end, align),
+            (None, CompKind::Union) => 0,
+        };
+        end = member_offset + size;
+        let offset = data_offset + member_offset;
+
+        match m {
             &CompMember::Field(ref f) => {
-                methods.extend(mk_field_method(ctx, f, offset).into_iter());
-                f.ty.size()
+                if f.bitfields.is_some() {
+                    methods.extend(gen_bitfield_methods(ctx, BitfieldUnit::Blob(data_field, offset),
+                                                        f, &f.ty, None).into_iter());
+                } else {
+                    methods.extend(mk_field_method(ctx, f, offset).into_iter());
+                }
             }
             &CompMember::Comp(ref rc_c) => {
                 let ref c = rc_c.borrow();
                 methods.extend(gen_comp_methods(ctx, data_field, offset, c.kind,
                                                 &c.members, extra).into_iter());
-                c.layout.size
             }
             &CompMember::CompField(ref rc_c, ref f) => {
                 methods.extend(mk_field_method(ctx, f, offset).into_iter());
 
                 let c = rc_c.borrow();
-                extra.extend(comp_to_rs(ctx, c.kind, comp_name(c.kind, &c.name),
-                                        c.layout, c.members.clone()).into_iter());
-                f.ty.size()
+                extra.extend(comp_to_rs(ctx, comp_name(c.kind, &c.name), c.clone()).into_iter());
             }
-        };
-        match kind {
-            CompKind::Struct => { offset += advance_by; }
-            CompKind::Union  => { }
         }
     }
     methods
 }
 
+fn align_to(offset: usize, align: usize) -> usize {
+    if align == 0 { offset } else { (offset + align - 1) / align * align }
+}
+
+/// Returns the offset rustc will give a field with the given alignment that
+/// follows a field ending at `end`.  When clang places it further, an
+/// explicit padding field is added to `fields` first.  A warning is emitted
+/// if the C offset cannot be matched.
+fn pad_to_offset(ctx: &mut GenCtx, struct_name: &str, field_name: &str,
+                 fields: &mut Vec<ast::StructField>, padding: &mut u32,
+                 end: usize, offset: Option<usize>, align: usize) -> usize {
+    let natural = align_to(end, align);
+    let offset = match offset {
+        Some(offset) => offset,
+        None => return natural
+    };
+
+    let mut rust_offset = natural;
+    if offset > natural {
+        *padding += 1;
+        let name = format!("_bindgen_padding_{}_", *padding);
+        fields.push(mk_blob_field(ctx, &name[..], Layout::new(offset - end, 1)));
+        rust_offset = align_to(offset, align);
+    }
+    if rust_offset != offset {
+        let msg = format!("layout of `{}` diverges from C: field `{}` is at offset {} \
+                           in Rust, {} in C", struct_name, field_name, rust_offset, offset);
+        ctx.logger.log(&Diagnostic::new(Severity::Warning, Category::Layout, msg));
+    }
+    rust_offset
+}
+
+/// The first byte holding a group of bitfields and the byte after the last
+/// one.
+fn bitfield_bytes(f: &FieldInfo) -> (usize, usize) {
+    let start = f.offset.unwrap_or(0) / 8;
+    let bitfields = match f.bitfields {
+        Some(ref bitfields) => bitfields,
+        None => return (start, start + f.ty.size())
+    };
+    let first = bitfields.iter().map(|&(_, _, o)| o as usize).min().unwrap_or(0);
+    let last = bitfields.iter().map(|&(_, w, o)| (o + w) as usize).max().unwrap_or(0);
+    (start + first / 8, start + (last + 7) / 8)
+}
+
+/// The storage unit of a group of bitfields following a field that ends at
+/// `end`.
+///
+/// Clang reports the offset of a unit of the declared type of the first
+/// bitfield, which overlaps the fields before it when that bitfield doesn't
+/// start at bit 0 of the unit, and which doesn't hold all of the bitfields
+/// when packing lets them straddle it.  The unit is then narrowed down to an
+/// integer covering exactly the bytes of the bitfields.  `None` if there is
+/// no such integer.
+fn bitfield_unit(f: &FieldInfo, end: usize, pack: Option<usize>) -> Option<FieldInfo> {
+    let (kind, layout) = match f.ty {
+        TInt(kind, layout) => (kind, layout),
+        _ => return Some(f.clone())
+    };
+    let bitfields = match (&f.bitfields, f.offset) {
+        (&Some(ref bitfields), Some(_)) => bitfields,
+        _ => return Some(f.clone())
+    };
+    let unit_bits = layout.size * 8;
+    let fits = bitfields.iter().all(|&(_, w, o)| (o + w) as usize <= unit_bits);
+    let (first, last) = bitfield_bytes(f);
+    if fits && unit_bits <= 64 && f.offset.unwrap() / 8 >= end {
+        return Some(f.clone());
+    }
+
+    let size = last - first;
+    let align = match pack {
+        Some(pack) => cmp::min(size, pack),
+        None => size
+    };
+    if first < end || align == 0 || first % align != 0 {
+        return None;
+    }
+    let kind = match (size, kind.is_signed()) {
+        (1, true) => ISChar,
+        (1, false) => IUChar,
+        (2, true) => IShort,
+        (2, false) => IUShort,
+        (4, true) => IInt,
+        (4, false) => IUInt,
+        (8, true) => ILongLong,
+        (8, false) => IULongLong,
+        _ => return None
+    };
+    let shift = ((first - f.offset.unwrap() / 8) * 8) as u32;
+    let mut unit = f.clone();
+    unit.ty = TInt(kind, Layout::new(size, size));
+    unit.offset = Some(first * 8);
+    unit.bitfields = Some(bitfields.iter().map(|&(ref n, w, o)| (n.clone(), w, o - shift)).collect());
+    Some(unit)
+}
+
+/// Where the storage unit of a group of bitfields lives.
+enum BitfieldUnit<'a> {
+    /// A field of the generated struct.
+    Field(&'a str),
+    /// A byte offset into an untyped data field, used for bitfields of
+    /// anonymous nested structs and unions.
+    Blob(&'a str, usize),
+}
+
+/// Generates a getter and a setter of type `ty` for every named bitfield
+/// stored in `f` and, when `ctor` is given, a function of that name packing
+/// values for all of them into a new storage unit.
+///
+/// Bitfields are allocated from the least significant bit of the unit on
+/// little endian targets and from the most significant one on big endian
+/// targets, so the shift is chosen with `cfg!(target_endian)`.
+fn gen_bitfield_methods(ctx: &mut GenCtx, unit: BitfieldUnit, f: &FieldInfo, ty: &Type,
+                        ctor: Option<&str>) -> Vec<P<ast::ImplItem>> {
+    let (layout, kind) = match (&f.ty, ty) {
+        (&TInt(_, layout), &TInt(kind, _)) => (layout, kind),
+        // Bitfields of other types are reported by the parser
+        _ => return vec!(),
+    };
+    let bitfields = match f.bitfields {
+        Some(ref bitfields) => bitfields,
+        None => return vec!(),
+    };
+
+    let unit_bits = (layout.size * 8) as u32;
+    if unit_bits > 64 || bitfields.iter().any(|&(_, w, o)| o.checked_add(w).map_or(true, |e| e > unit_bits)) {
+        let names: Vec<String> = bitfields.iter().filter(|&&(ref n, _, _)| !n.is_empty())
+            .map(|&(ref n, _, _)| format!("`{}`", n)).collect();
+        let msg = format!("bitfields {} don't fit in their storage unit, they have no accessors",
+                          names.connect(", "));
+        ctx.logger.log(&Diagnostic::new(Severity::Warning, Category::Layout, msg));
+        return vec!();
+    }
+    let unit_ty = tts_to_string(&P(cty_to_rs(ctx, &f.ty)).to_tokens(&ctx.ext_cx)[..]);
+    let ty = tts_to_string(&P(cty_to_rs(ctx, ty)).to_tokens(&ctx.ext_cx)[..]);
+    let (unsafety, read, write_prefix) = match unit {
+        BitfieldUnit::Field(name) => {
+            ("", format!("self.{} as u64", name), format!("self.{} = ", name))
+        }
+        BitfieldUnit::Blob(name, offset) => {
+            let read = format!("{{
+                let raw: *const u8 = ::std::mem::transmute(&self.{});
+                *(raw.offset({}) as *const {}) as u64
+            }}", name, offset, unit_ty);
+            let write_prefix = format!("let raw: *mut u8 = ::std::mem::transmute(&mut self.{});
+                *(raw.offset({}) as *mut {}) = ", name, offset, unit_ty);
+            ("unsafe ", read, write_prefix)
+        }
+    };
+
+    let mut src = String::new();
+    let mut ctor_args = vec!();
+    let mut ctor_body = String::new();
+    for &(ref name, width, offset) in bitfields.iter() {
+        let le_shift = offset;
+        let be_shift = unit_bits - offset - width;
+        if name.is_empty() {
+            continue;
+        }
+
+        let f_name = first(rust_id(ctx, name.clone()));
+        let mask = if width >= 64 { !0u64 } else { (1u64 << width) - 1 };
+        let shift = format!("if cfg!(target_endian = \"big\") {{ {} }} else {{ {} }}",
+                            be_shift, le_shift);
+        let get = if kind.is_signed() {
+            format!("(((unit << (64 - {} - shift)) as i64) >> (64 - {})) as {}", width, width, ty)
+        } else {
+            format!("((unit >> shift) & {:#x}u64) as {}", mask, ty)
+        };
+
+        src.push_str(&format!("
+            #[inline]
+            pub {unsafety}fn {name}(&self) -> {ty} {{
+                let unit = {read};
+                let shift = {shift};
+                {get}
+            }}
+            #[inline]
+            pub {unsafety}fn set_{name}(&mut self, val: {ty}) {{
+                let unit = {read};
+                let shift = {shift};
+                let mask = {mask:#x}u64 << shift;
+                {write}((unit & !mask) | ((val as u64) << shift & mask)) as {unit_ty};
+            }}
+        ", unsafety = unsafety, name = f_name, ty = ty, unit_ty = unit_ty, read = read,
+           shift = shift, get = get, mask = mask, write = write_prefix)[..]);
+
+        ctor_args.push(format!("{}: {}", f_name, ty));
+        ctor_body.push_str(&format!("
+            unit |= ((({} as u64) & {:#x}u64) << ({}));", f_name, mask, shift)[..]);
+    }
+
+    if let Some(ctor) = ctor {
+        if !ctor_args.is_empty() {
+            src.push_str(&format!("
+                #[inline]
+                pub fn {}({}) -> {} {{
+                    let mut unit = 0u64;{}
+                    unit as {}
+                }}
+            ", ctor, ctor_args.connect(", "), unit_ty, ctor_body, unit_ty)[..]);
+        }
+    }
+
+    let impl_str = format!("impl X {{ {} }}", src);
+    let item = parse::new_parser_from_source_str(ctx.ext_cx.parse_sess(),
+        ctx.ext_cx.cfg(), "".to_string(), impl_str).parse_item().unwrap();
+    item.and_then(|i| {
+        match i.node {
+            ast::ItemImpl(_, _, _, _, _, items) => items,
+            _ => unreachable!("impl parsed to something other than impl")
+        }
+    })
+}
+
+/// Generates a test checking the size and alignment of a struct or union
+/// and the offsets of its fields against the values clang reported.
+///
+/// The fields of a struct are located with `addr_of!`, which unlike taking a
+/// reference is allowed for the fields of packed structs.  The members of a
+/// union are located through their accessors when `accessors` is set.
+fn mk_layout_test(ctx: &mut GenCtx, name: &str, layout: Layout,
+                  offsets: &[(String, usize)], accessors: bool) -> P<ast::Item> {
+    let id = rust_type_id(ctx, name.to_string());
+    let mut checks = String::new();
+    if !offsets.is_empty() {
+        checks.push_str(&format!("
+            unsafe {{
+                let {}val: {} = ::std::mem::zeroed();
+                let base = ::std::ptr::addr_of!(val) as usize;",
+            if accessors { "mut " } else { "" }, id)[..]);
+        for &(ref field, offset) in offsets.iter() {
+            let addr = if accessors {
+                format!("val.{}() as usize", field)
+            } else {
+                format!("::std::ptr::addr_of!(val.{}) as usize", field)
+            };
+            checks.push_str(&format!("
+                assert_eq!({} - base, {}usize);", addr, offset)[..]);
+        }
+        checks.push_str("
+            }");
+    }
+
+    let test_str = format!("
+        #[test]
+        fn bindgen_test_layout_{id}() {{
+            assert_eq!(::std::mem::size_of::<{id}>(), {size}usize);
+            assert_eq!(::std::mem::align_of::<{id}>(), {align}usize);{checks}
+        }}
+    ", id = id, size = layout.size, align = layout.align, checks = checks);
+
+    parse::new_parser_from_source_str(ctx.ext_cx.parse_sess(),
+        ctx.ext_cx.cfg(), "".to_string(), test_str).parse_item().unwrap()
+}
+
+/// Generates `__BindgenLongDouble`, an opaque blob with the size and
+/// alignment of a C `long double`.
+fn mk_long_double(ctx: &mut GenCtx, layout: Layout) -> Vec<P<ast::Item>> {
+    let name = "__BindgenLongDouble";
+    let def = ast::ItemStruct(
+        P(ast::StructDef {
+           fields: vec!(mk_blob_field(ctx, "_bindgen_data_", layout)),
+           ctor_id: None,
+        }),
+        empty_generics()
+    );
+
+    let over_align = if layout.align > blob_align(layout) { Some(layout.align) } else { None };
+    let mut attrs = mk_doc_attrs(ctx, "Storage for a C `long double`, which has no Rust equivalent.");
+    attrs.push(mk_repr_attr(ctx, None, over_align));
+    attrs.push(mk_deriving_copy_attr(ctx));
+    let def = P(ast::Item {
+        ident: ctx.ext_cx.ident_of(name),
+        attrs: attrs,
+        id: ast::DUMMY_NODE_ID,
+        node: def,
+        vis: ast::Public,
+        span: ctx.span
+    });
+
+    vec!(def, mk_clone_impl(ctx, name), mk_default_impl(ctx, name))
+}
+
+/// Generates `__BindgenComplex<T>`, laid out like a C `_Complex` number
+/// whose real and imaginary parts are of type `T`.
+fn mk_complex(ctx: &mut GenCtx) -> P<ast::Item> {
+    let complex_str = r"
+        /// A C `_Complex` number with real and imaginary parts of type `T`.
+        #[repr(C)]
+        #[derive(Copy, Clone, Default)]
+        pub struct __BindgenComplex<T> {
+            pub re: T,
+            pub im: T,
+        }
+    ".to_string();
+
+    parse::new_parser_from_source_str(ctx.ext_cx.parse_sess(),
+        ctx.ext_cx.cfg(), "".to_string(), complex_str).parse_item().unwrap()
+}
+
+/// Generates `id`, the type of Objective-C objects.
+fn mk_objc_id(ctx: &mut GenCtx) -> P<ast::Item> {
+    let id_str = r"
+        /// An Objective-C object, to which messages are sent with the `objc`
+        /// crate's `msg_send!`.
+        pub type id = *mut ::objc::runtime::Object;
+    ".to_string();
+
+    parse::new_parser_from_source_str(ctx.ext_cx.parse_sess(),
+        ctx.ext_cx.cfg(), "".to_string(), id_str).parse_item().unwrap()
+}
+
 // Implements std::default::Default using std::mem::zeroed.
 fn mk_default_impl(ctx: &GenCtx, ty_name: &str) -> P<ast::Item> {
     let impl_str = format!(r"
@@ -772,15 +1916,25 @@ fn mk_clone_impl(ctx: &GenCtx, ty_name: &str) -> P<ast::Item> {
         ctx.ext_cx.cfg(), "".to_string(), impl_str).parse_item().unwrap()
 }
 
+/// The alignment rustc gives a blob field generated for `layout`.  Larger
+/// alignments have to be requested on the containing struct.
+fn blob_align(layout: Layout) -> usize {
+    match layout.align {
+        1 | 2 | 4 | 8 => layout.align,
+        align if align > 8 => 8,
+        _ => 1,
+    }
+}
+
 fn mk_blob_field(ctx: &GenCtx, name: &str, layout: Layout) -> Spanned<ast::StructField_> {
-    let ty_name = match layout.align {
-        1 => "u8",
+    let align = blob_align(layout);
+    let ty_name = match align {
         2 => "u16",
         4 => "u32",
         8 => "u64",
         _ => "u8",
     };
-    let data_len = if ty_name == "u8" { layout.size } else { layout.size / layout.align };
+    let data_len = layout.size / align;
     let base_ty = mk_ty(ctx, false, vec!(ty_name.to_string()));
     let data_ty = P(mk_arrty(ctx, &base_ty, data_len));
     respan(ctx.span, ast::StructField_ {
@@ -794,6 +1948,62 @@ fn mk_blob_field(ctx: &GenCtx, name: &str, layout: Layout) -> Spanned<ast::Struc
     })
 }
 
+fn mk_doc_attrs(ctx: &mut GenCtx, comment: &str) -> Vec<ast::Attribute> {
+    let mut attrs = vec!();
+    if comment.is_empty() {
+        return attrs;
+    }
+    for line in comment.lines() {
+        let lit = respan(ctx.span, ast::LitStr(
+            to_intern_str(ctx, line.to_string()),
+            ast::CookedStr
+        ));
+        let attr_val = P(respan(ctx.span, ast::MetaNameValue(
+            to_intern_str(ctx, "doc".to_string()), lit
+        )));
+        attrs.push(respan(ctx.span, ast::Attribute_ {
+            id: mk_attr_id(),
+            style: ast::AttrOuter,
+            value: attr_val,
+            is_sugared_doc: false
+        }));
+    }
+    attrs
+}
+
+/// The attributes of the item for the variable or function `v`: its
+/// documentation, and `#[deprecated]` and `#[must_use]` when the C
+/// declaration has the matching attribute.
+fn mk_var_attrs(ctx: &mut GenCtx, v: &VarInfo) -> Vec<ast::Attribute> {
+    let mut attrs = mk_doc_attrs(ctx, &v.comment[..]);
+    let mut metas = vec!();
+    if let Some(ref msg) = v.deprecated {
+        let word = to_intern_str(ctx, "deprecated".to_string());
+        metas.push(if msg.is_empty() {
+            ast::MetaWord(word)
+        } else {
+            let lit = respan(ctx.span, ast::LitStr(
+                to_intern_str(ctx, msg.clone()),
+                ast::CookedStr
+            ));
+            let note = ast::MetaNameValue(to_intern_str(ctx, "note".to_string()), lit);
+            ast::MetaList(word, vec!(P(respan(ctx.span, note))))
+        });
+    }
+    if v.must_use {
+        metas.push(ast::MetaWord(to_intern_str(ctx, "must_use".to_string())));
+    }
+    for meta in metas.into_iter() {
+        attrs.push(respan(ctx.span, ast::Attribute_ {
+            id: mk_attr_id(),
+            style: ast::AttrOuter,
+            value: P(respan(ctx.span, meta)),
+            is_sugared_doc: false
+        }));
+    }
+    attrs
+}
+
 fn mk_link_name_attr(ctx: &mut GenCtx, name: String) -> ast::Attribute {
     let lit = respan(ctx.span, ast::LitStr(
         to_intern_str(ctx, name),
@@ -811,10 +2021,31 @@ fn mk_link_name_attr(ctx: &mut GenCtx, name: String) -> ast::Attribute {
     respan(ctx.span, attr)
 }
 
-fn mk_repr_attr(ctx: &mut GenCtx) -> ast::Attribute {
+/// Builds `#[repr(C)]`, with `packed(N)` or `align(N)` added when the record
+/// is packed or aligned beyond what its fields require.
+fn mk_repr_attr(ctx: &mut GenCtx, pack: Option<usize>, align: Option<usize>) -> ast::Attribute {
+    fn mk_word(ctx: &mut GenCtx, word: String) -> P<ast::MetaItem> {
+        P(respan(ctx.span, ast::MetaWord(to_intern_str(ctx, word))))
+    }
+
+    fn mk_sized(ctx: &mut GenCtx, name: &str, n: usize) -> P<ast::MetaItem> {
+        let n = mk_word(ctx, n.to_string());
+        P(respan(ctx.span, ast::MetaList(to_intern_str(ctx, name.to_string()), vec!(n))))
+    }
+
+    let mut reprs = vec!(mk_word(ctx, "C".to_string()));
+    match pack {
+        Some(1) => reprs.push(mk_word(ctx, "packed".to_string())),
+        Some(n) => reprs.push(mk_sized(ctx, "packed", n)),
+        None => ()
+    }
+    if let Some(n) = align {
+        reprs.push(mk_sized(ctx, "align", n));
+    }
+
     let attr_val = P(respan(ctx.span, ast::MetaList(
         to_intern_str(ctx, "repr".to_string()),
-        vec!(P(respan(ctx.span, ast::MetaWord(to_intern_str(ctx, "C".to_string())))))
+        reprs
     )));
 
     respan(ctx.span, ast::Attribute_ {
@@ -840,13 +2071,16 @@ fn mk_deriving_copy_attr(ctx: &mut GenCtx) -> ast::Attribute {
 }
 
 fn cvar_to_rs(ctx: &mut GenCtx, name: String,
+                                mangled: Option<String>,
+                                mut attrs: Vec<ast::Attribute>,
                                 ty: &Type,
                                 is_const: bool) -> P<ast::ForeignItem> {
     let (rust_name, was_mangled) = rust_id(ctx, name.clone());
 
-    let mut attrs = Vec::new();
-    if was_mangled {
-        attrs.push(mk_link_name_attr(ctx, name));
+    match mangled {
+        Some(mangled) => attrs.push(mk_link_name_attr(ctx, mangled)),
+        None if was_mangled => attrs.push(mk_link_name_attr(ctx, name)),
+        None => ()
     }
 
     return P(ast::ForeignItem {
@@ -859,19 +2093,92 @@ fn cvar_to_rs(ctx: &mut GenCtx, name: String,
            });
 }
 
+fn cconst_to_rs(ctx: &mut GenCtx, name: String, attrs: Vec<ast::Attribute>,
+                ty: &Type, val: &ConstVal) -> P<ast::Item> {
+    let (rust_ty, expr) = match *val {
+        ConstVal::Int(v) => {
+            let (abs, sign) = if v < 0 {
+                ((v as u64).wrapping_neg(), ast::Minus)
+            } else {
+                (v as u64, ast::Plus)
+            };
+            let lit = ast::LitInt(abs, ast::UnsuffixedIntLit(sign));
+            (P(cty_to_rs(ctx, ty)), ctx.ext_cx.expr_lit(ctx.span, lit))
+        }
+        ConstVal::UInt(v) => {
+            let lit = ast::LitInt(v, ast::UnsuffixedIntLit(ast::Plus));
+            (P(cty_to_rs(ctx, ty)), ctx.ext_cx.expr_lit(ctx.span, lit))
+        }
+        ConstVal::Float(v) => {
+            let expr = if v.is_finite() {
+                let mut s = format!("{}", v);
+                if !s.contains('.') && !s.contains('e') {
+                    s.push_str(".0");
+                }
+                let lit = ast::LitFloatUnsuffixed(to_intern_str(ctx, s));
+                ctx.ext_cx.expr_lit(ctx.span, lit)
+            } else {
+                let float_ty = match *ty {
+                    TFloat(FFloat, _) => "f32",
+                    _ => "f64",
+                };
+                let cst = if v.is_nan() {
+                    "NAN"
+                } else if v < 0.0 {
+                    "NEG_INFINITY"
+                } else {
+                    "INFINITY"
+                };
+                let path = ctx.ext_cx.path_global(ctx.span, vec!(
+                    ctx.ext_cx.ident_of("std"),
+                    ctx.ext_cx.ident_of(float_ty),
+                    ctx.ext_cx.ident_of(cst)
+                ));
+                ctx.ext_cx.expr_path(path)
+            };
+            (P(cty_to_rs(ctx, ty)), expr)
+        }
+        ConstVal::Str(ref bytes) => {
+            // String constants become references to nul terminated byte
+            // arrays, ready to be passed where C expects a `const char *`.
+            let elem_ty = mk_ty(ctx, false, vec!("u8".to_string()));
+            let arr_ty = mk_arrty(ctx, &elem_ty, bytes.len());
+            let lifetime = ctx.ext_cx.lifetime(ctx.span, token::intern("'static"));
+            let ref_ty = ctx.ext_cx.ty_rptr(ctx.span, P(arr_ty), Some(lifetime), ast::MutImmutable);
+            let lit = ast::LitBinary(Rc::new(bytes.clone()));
+            (ref_ty, ctx.ext_cx.expr_lit(ctx.span, lit))
+        }
+    };
+
+    let id = first(rust_id(ctx, name));
+    P(ast::Item {
+        ident: ctx.ext_cx.ident_of(&id[..]),
+        attrs: attrs,
+        id: ast::DUMMY_NODE_ID,
+        node: ast::ItemConst(rust_ty, expr),
+        vis: ast::Public,
+        span: ctx.span
+    })
+}
+
 fn cfuncty_to_rs(ctx: &mut GenCtx,
                  rty: &Type,
                  aty: &[(String, Type)],
-                 var: bool) -> ast::FnDecl {
+                 var: bool,
+                 noreturn: bool) -> ast::FnDecl {
 
-    let ret = P(match *rty {
-        TVoid => ast::Ty {
-            id: ast::DUMMY_NODE_ID,
-            node: ast::TyTup(vec![]),
-            span: ctx.span
-        },
-        _ => cty_to_rs(ctx, rty)
-    });
+    let ret = if noreturn {
+        ast::NoReturn(ctx.span)
+    } else {
+        ast::Return(P(match *rty {
+            TVoid => ast::Ty {
+                id: ast::DUMMY_NODE_ID,
+                node: ast::TyTup(vec![]),
+                span: ctx.span
+            },
+            _ => cty_to_rs(ctx, rty)
+        }))
+    };
 
     let mut unnamed: usize = 0;
     let args: Vec<ast::Arg> = aty.iter().map(|arg| {
@@ -912,25 +2219,26 @@ fn cfuncty_to_rs(ctx: &mut GenCtx,
     let var = !args.is_empty() && var;
     return ast::FnDecl {
         inputs: args,
-        output: ast::Return(ret),
+        output: ret,
         variadic: var
     };
 }
 
-fn cfunc_to_rs(ctx: &mut GenCtx, name: String, rty: &Type,
-               aty: &[(String, Type)],
-               var: bool) -> P<ast::ForeignItem> {
-    let var = !aty.is_empty() && var;
+fn cfunc_to_rs(ctx: &mut GenCtx, name: String, mangled: Option<String>,
+               mut attrs: Vec<ast::Attribute>,
+               sig: &FuncSig) -> P<ast::ForeignItem> {
+    let var = !sig.args.is_empty() && sig.is_variadic;
     let decl = ast::ForeignItemFn(
-        P(cfuncty_to_rs(ctx, rty, aty, var)),
+        P(cfuncty_to_rs(ctx, &*sig.ret_ty, &sig.args[..], var, sig.noreturn)),
         empty_generics()
     );
 
     let (rust_name, was_mangled) = rust_id(ctx, name.clone());
 
-    let mut attrs = Vec::new();
-    if was_mangled {
-        attrs.push(mk_link_name_attr(ctx, name));
+    match mangled {
+        Some(mangled) => attrs.push(mk_link_name_attr(ctx, mangled)),
+        None if was_mangled => attrs.push(mk_link_name_attr(ctx, name)),
+        None => ()
     }
 
     return P(ast::ForeignItem {
@@ -944,6 +2252,10 @@ fn cfunc_to_rs(ctx: &mut GenCtx, name: String, rty: &Type,
 }
 
 fn cty_to_rs(ctx: &mut GenCtx, ty: &Type) -> ast::Ty {
+    if let Some(rust_ty) = replaced_ty(ctx, ty) {
+        return rust_ty;
+    }
+
     return match ty {
         &TVoid => mk_ty(ctx, true, vec!("libc".to_string(), "c_void".to_string())),
         &TInt(i, ref layout) => match i {
@@ -966,11 +2278,27 @@ fn cty_to_rs(ctx: &mut GenCtx, ty: &Type) -> ast::Ty {
             ILong => mk_ty(ctx, true, vec!("libc".to_string(), "c_long".to_string())),
             IULong => mk_ty(ctx, true, vec!("libc".to_string(), "c_ulong".to_string())),
             ILongLong => mk_ty(ctx, true, vec!("libc".to_string(), "c_longlong".to_string())),
-            IULongLong => mk_ty(ctx, true, vec!("libc".to_string(), "c_ulonglong".to_string()))
+            IULongLong => mk_ty(ctx, true, vec!("libc".to_string(), "c_ulonglong".to_string())),
+            IInt128 => mk_ty(ctx, false, vec!("i128".to_string())),
+            IUInt128 => mk_ty(ctx, false, vec!("u128".to_string()))
         },
-        &TFloat(f, _) => match f {
-            FFloat => mk_ty(ctx, true, vec!("libc".to_string(), "c_float".to_string())),
-            FDouble => mk_ty(ctx, true, vec!("libc".to_string(), "c_double".to_string()))
+        &TFloat(f, layout) => {
+            // The parts of a complex number each take half of it
+            let part = Layout::new(layout.size / 2, layout.align);
+            match f {
+                FFloat => mk_ty(ctx, true, vec!("libc".to_string(), "c_float".to_string())),
+                FDouble => mk_ty(ctx, true, vec!("libc".to_string(), "c_double".to_string())),
+                FLongDouble => {
+                    if ctx.long_double.is_none() {
+                        ctx.long_double = Some(layout);
+                    }
+                    let path = ns_path(ctx, &[], "__BindgenLongDouble".to_string());
+                    mk_ty(ctx, false, path)
+                },
+                FComplexFloat => mk_complex_ty(ctx, &TFloat(FFloat, part)),
+                FComplexDouble => mk_complex_ty(ctx, &TFloat(FDouble, part)),
+                FComplexLongDouble => mk_complex_ty(ctx, &TFloat(FLongDouble, part)),
+            }
         },
         &TPtr(ref t, is_const, _) => {
             let id = cty_to_rs(ctx, &**t);
@@ -981,30 +2309,85 @@ fn cty_to_rs(ctx: &mut GenCtx, ty: &Type) -> ast::Ty {
             mk_arrty(ctx, &ty, s)
         },
         &TFuncPtr(ref sig) => {
-            let decl = cfuncty_to_rs(ctx, &*sig.ret_ty, &sig.args[..], sig.is_variadic);
+            let decl = cfuncty_to_rs(ctx, &*sig.ret_ty, &sig.args[..], sig.is_variadic, sig.noreturn);
             mk_fnty(ctx, &decl, sig.abi)
         },
         &TFuncProto(ref sig) => {
-            let decl = cfuncty_to_rs(ctx, &*sig.ret_ty, &sig.args[..], sig.is_variadic);
+            let decl = cfuncty_to_rs(ctx, &*sig.ret_ty, &sig.args[..], sig.is_variadic, sig.noreturn);
             mk_fn_proto_ty(ctx, &decl, sig.abi)
         },
         &TNamed(ref ti) => {
-            let id = rust_type_id(ctx, ti.borrow().name.clone());
-            mk_ty(ctx, false, vec!(id))
+            let ti = ti.borrow();
+            let id = rust_type_id(ctx, ti.name.clone());
+            let path = ns_path(ctx, &ti.namespace[..], id);
+            mk_ty(ctx, false, path)
         },
         &TComp(ref ci) => {
             let mut c = ci.borrow_mut();
             c.name = unnamed_name(ctx, c.name.clone());
-            mk_ty(ctx, false, vec!(comp_name(c.kind, &c.name)))
+            let path = ns_path(ctx, &c.namespace[..], comp_name(c.kind, &c.name));
+            mk_ty(ctx, false, path)
         },
         &TEnum(ref ei) => {
             let mut e = ei.borrow_mut();
             e.name = unnamed_name(ctx, e.name.clone());
-            mk_ty(ctx, false, vec!(enum_name(&e.name)))
+            let mut path = ns_path(ctx, &e.namespace[..], enum_name(&e.name));
+            if enum_style(ctx, &e.name[..]) == EnumStyle::Module {
+                path.push("Type".to_string());
+            }
+            mk_ty(ctx, false, path)
+        },
+        &TObjC(kind, _) => match kind {
+            OId => {
+                ctx.objc = true;
+                let path = ns_path(ctx, &[], "id".to_string());
+                mk_ty(ctx, false, path)
+            },
+            OClass => {
+                let class = mk_ty(ctx, true, vec!("objc".to_string(), "runtime".to_string(),
+                                                  "Class".to_string()));
+                mk_ptrty(ctx, &class, false)
+            },
+            OSel => mk_ty(ctx, true, vec!("objc".to_string(), "runtime".to_string(),
+                                          "Sel".to_string())),
         }
     };
 }
 
+/// The Rust type the user asked to use instead of the named type `ty`.
+fn replaced_ty(ctx: &GenCtx, ty: &Type) -> Option<ast::Ty> {
+    let name = match ty {
+        &TNamed(ref ti) => ti.borrow().name.clone(),
+        &TComp(ref ci) => ci.borrow().name.clone(),
+        &TEnum(ref ei) => ei.borrow().name.clone(),
+        _ => return None
+    };
+    ctx.type_replacements.iter().find(|&&(ref c_name, _)| *c_name == name)
+                             .map(|&(_, ref rust_ty)| (**rust_ty).clone())
+}
+
+fn mk_complex_ty(ctx: &mut GenCtx, part: &Type) -> ast::Ty {
+    ctx.complex = true;
+    let part_ty = P(cty_to_rs(ctx, part));
+    let idents = ns_path(ctx, &[], "__BindgenComplex".to_string()).iter().map(|s| {
+        ctx.ext_cx.ident_of(&s[..])
+    }).collect();
+    let path = ctx.ext_cx.path_all(ctx.span, false, idents, vec!(), vec!(part_ty), vec!());
+    ctx.ext_cx.ty_path(path).and_then(|ty| ty)
+}
+
+/// The path to `name`, declared in the C++ namespace `ns`, from the module
+/// of the items being generated.
+fn ns_path(ctx: &mut GenCtx, ns: &[String], name: String) -> Vec<String> {
+    let common = ctx.module.iter().zip(ns.iter()).take_while(|&(a, b)| a == b).count();
+    let mut path: Vec<String> = (common..ctx.module.len()).map(|_| "super".to_string()).collect();
+    for n in ns[common..].iter() {
+        path.push(first(rust_id(ctx, n.clone())));
+    }
+    path.push(name);
+    path
+}
+
 fn mk_ty(ctx: &GenCtx, global: bool, segments: Vec<String>) -> ast::Ty {
     let ty = ast::TyPath(
         None,
@@ -3,17 +3,23 @@
 
 extern crate syntex_syntax as syntax;
 extern crate libc;
+extern crate regex;
 #[macro_use] extern crate log;
 
+use std::cell::RefCell;
 use std::collections::HashSet;
 use std::default::Default;
+use std::error;
+use std::fmt;
 use std::io::{Write, self};
 use std::fs::OpenOptions;
 use std::path::{Path, self};
+use std::str::FromStr;
 use std::{env, fs};
 
 use syntax::ast;
 use syntax::codemap::{DUMMY_SP, Span};
+use syntax::parse;
 use syntax::print::pprust;
 use syntax::print::pp::eof;
 use syntax::ptr::P;
@@ -24,6 +30,7 @@ mod types;
 mod clangll;
 mod clang;
 mod gen;
+mod macros;
 mod parser;
 
 #[derive(Clone)]
@@ -66,6 +73,46 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Only binds functions whose whole name matches one of the given
+    /// regexes.
+    pub fn allowlist_function<T: Into<String>>(&mut self, pattern: T) -> &mut Self {
+        self.options.allowlist_functions.push(pattern.into());
+        self
+    }
+
+    /// Skips functions whose whole name matches the regex.
+    pub fn blocklist_function<T: Into<String>>(&mut self, pattern: T) -> &mut Self {
+        self.options.blocklist_functions.push(pattern.into());
+        self
+    }
+
+    /// Only binds structs, unions, enums and typedefs whose whole name
+    /// matches one of the given regexes.
+    pub fn allowlist_type<T: Into<String>>(&mut self, pattern: T) -> &mut Self {
+        self.options.allowlist_types.push(pattern.into());
+        self
+    }
+
+    /// Skips structs, unions, enums and typedefs whose whole name matches
+    /// the regex.
+    pub fn blocklist_type<T: Into<String>>(&mut self, pattern: T) -> &mut Self {
+        self.options.blocklist_types.push(pattern.into());
+        self
+    }
+
+    /// Only binds variables whose whole name matches one of the given
+    /// regexes.
+    pub fn allowlist_var<T: Into<String>>(&mut self, pattern: T) -> &mut Self {
+        self.options.allowlist_vars.push(pattern.into());
+        self
+    }
+
+    /// Skips variables whose whole name matches the regex.
+    pub fn blocklist_var<T: Into<String>>(&mut self, pattern: T) -> &mut Self {
+        self.options.blocklist_vars.push(pattern.into());
+        self
+    }
+
     pub fn forbid_unknown_types(&mut self) -> &mut Self {
         self.options.fail_on_unknown_type = true;
         self
@@ -76,12 +123,67 @@ impl<'a> Builder<'a> {
         self
     }
 
+    pub fn enum_style(&mut self, style: EnumStyle) -> &mut Self {
+        self.options.enum_style = style;
+        self
+    }
+
+    /// Uses `style` for the enums whose name matches the `pattern` regex,
+    /// instead of the default one.
+    pub fn enum_style_for<T: Into<String>>(&mut self, pattern: T, style: EnumStyle) -> &mut Self {
+        self.options.enum_styles.push((pattern.into(), style));
+        self
+    }
+
+    /// Binds the structs and unions that allowlisted items only need behind
+    /// a pointer as opaque types.
+    pub fn opaque_behind_pointers(&mut self) -> &mut Self {
+        self.options.opaque_behind_pointers = true;
+        self
+    }
+
+    /// Binds the structs and unions whose whole name matches the regex as a
+    /// blob of the right size and alignment, hiding their fields.
+    pub fn opaque_type<T: Into<String>>(&mut self, pattern: T) -> &mut Self {
+        self.options.opaque_types.push(pattern.into());
+        self
+    }
+
+    /// Uses the Rust type `rust_ty`, e.g. `::libc::timespec`, wherever the C
+    /// struct, union, enum or typedef named `c_name` is referred to, instead
+    /// of generating it.
+    pub fn replace_type<T: Into<String>, U: Into<String>>(&mut self, c_name: T, rust_ty: U) -> &mut Self {
+        self.options.type_replacements.push((c_name.into(), rust_ty.into()));
+        self
+    }
+
+    /// Binds `static` functions, `static inline` helpers included, through
+    /// exported wrappers whose C source is available from the bindings.
+    pub fn wrap_static_fns(&mut self) -> &mut Self {
+        self.options.wrap_static_fns = true;
+        self
+    }
+
+    /// Binds the function-like macro declared by `prototype`, e.g.
+    /// `unsigned FOO_GET_FLAGS(const struct foo *x)`, through an exported
+    /// wrapper whose C source is available from the bindings.  The types
+    /// are those of the main header.
+    pub fn wrap_macro<T: Into<String>>(&mut self, prototype: T) -> &mut Self {
+        self.options.wrap_macros.push(prototype.into());
+        self
+    }
+
+    pub fn emit_layout_tests(&mut self) -> &mut Self {
+        self.options.layout_tests = true;
+        self
+    }
+
     pub fn log(&mut self, logger: &'a Logger) -> &mut Self {
         self.logger = Some(logger);
         self
     }
 
-    pub fn generate(&self) -> Result<Bindings, ()> {
+    pub fn generate(&self) -> Result<Bindings, Error> {
         Bindings::generate(&self.options, self.logger, None)
     }
 }
@@ -105,6 +207,20 @@ pub struct BindgenOptions {
     pub fail_on_unknown_type: bool,
     pub override_enum_ty: String,
     pub clang_args: Vec<String>,
+    pub layout_tests: bool,
+    pub enum_style: EnumStyle,
+    pub enum_styles: Vec<(String, EnumStyle)>,
+    pub allowlist_functions: Vec<String>,
+    pub blocklist_functions: Vec<String>,
+    pub allowlist_types: Vec<String>,
+    pub blocklist_types: Vec<String>,
+    pub allowlist_vars: Vec<String>,
+    pub blocklist_vars: Vec<String>,
+    pub opaque_behind_pointers: bool,
+    pub opaque_types: Vec<String>,
+    pub type_replacements: Vec<(String, String)>,
+    pub wrap_static_fns: bool,
+    pub wrap_macros: Vec<String>,
 }
 
 impl Default for BindgenOptions {
@@ -116,7 +232,84 @@ impl Default for BindgenOptions {
             emit_ast: false,
             fail_on_unknown_type: false,
             override_enum_ty: "".to_string(),
-            clang_args: Vec::new()
+            clang_args: Vec::new(),
+            layout_tests: false,
+            enum_style: EnumStyle::Consts,
+            enum_styles: Vec::new(),
+            allowlist_functions: Vec::new(),
+            blocklist_functions: Vec::new(),
+            allowlist_types: Vec::new(),
+            blocklist_types: Vec::new(),
+            allowlist_vars: Vec::new(),
+            blocklist_vars: Vec::new(),
+            opaque_behind_pointers: false,
+            opaque_types: Vec::new(),
+            type_replacements: Vec::new(),
+            wrap_static_fns: false,
+            wrap_macros: Vec::new(),
+        }
+    }
+}
+
+impl BindgenOptions {
+    /// Sets an enum style given as `[<pattern>=]<style>`, the form accepted
+    /// on the command line and by the `bindgen!` macro.
+    pub fn parse_enum_style(&mut self, arg: &str) -> Result<(), ()> {
+        let (pattern, style) = match arg.rfind('=') {
+            Some(i) => (Some(&arg[..i]), &arg[i + 1..]),
+            None => (None, arg)
+        };
+        let style = try!(style.parse());
+        match pattern {
+            Some(pattern) => self.enum_styles.push((pattern.to_string(), style)),
+            None => self.enum_style = style
+        }
+        Ok(())
+    }
+
+    /// Adds a type replacement given as `<c name>=<rust type>`, the form
+    /// accepted on the command line and by the `bindgen!` macro.
+    pub fn parse_type_replacement(&mut self, arg: &str) -> Result<(), ()> {
+        match arg.find('=') {
+            Some(i) if i > 0 && i + 1 < arg.len() => {
+                self.type_replacements.push((arg[..i].to_string(), arg[i + 1..].to_string()));
+                Ok(())
+            }
+            _ => Err(())
+        }
+    }
+}
+
+/// How C enums are represented.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EnumStyle {
+    /// A type alias of the underlying integer type and a constant for each
+    /// enumerator.
+    Consts,
+    /// A `#[repr]` Rust enum.  Enumerators repeating the value of an earlier
+    /// one become associated constants.
+    Rust,
+    /// A module holding a `Type` alias and a constant for each enumerator.
+    Module,
+    /// A newtype of the integer type with the enumerators as associated
+    /// constants.
+    NewType,
+    /// Like `NewType`, with the bitwise operators implemented so that the
+    /// constants can be combined as flags.
+    Bitflags,
+}
+
+impl FromStr for EnumStyle {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<EnumStyle, ()> {
+        match s {
+            "consts"  => Ok(EnumStyle::Consts),
+            "rust"    => Ok(EnumStyle::Rust),
+            "module"  => Ok(EnumStyle::Module),
+            "newtype" => Ok(EnumStyle::NewType),
+            "bitflags" => Ok(EnumStyle::Bitflags),
+            _         => Err(()),
         }
     }
 }
@@ -131,16 +324,261 @@ pub enum LinkType {
 pub trait Logger {
     fn error(&self, msg: &str);
     fn warn(&self, msg: &str);
+
+    /// Reports a diagnostic.  By default, errors go to `error`, warnings
+    /// and notes to `warn`, with their location appended, and the rest is
+    /// dropped.
+    fn log(&self, diag: &Diagnostic) {
+        let msg = match diag.location {
+            Some(ref location) => format!("{} ({})", diag.message, location),
+            None => diag.message.clone()
+        };
+        match diag.severity {
+            Severity::Debug | Severity::Info => (),
+            Severity::Note | Severity::Warning => self.warn(&msg[..]),
+            Severity::Error | Severity::Fatal => self.error(&msg[..]),
+        }
+    }
+}
+
+/// A logger keeping every diagnostic, for them to be inspected once the
+/// bindings are generated.
+pub struct CollectingLogger {
+    diags: RefCell<Vec<Diagnostic>>,
+}
+
+impl CollectingLogger {
+    pub fn new() -> CollectingLogger {
+        CollectingLogger { diags: RefCell::new(vec!()) }
+    }
+
+    /// The diagnostics reported so far, in order.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.diags.borrow().clone()
+    }
+
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.diags.into_inner()
+    }
+}
+
+impl Default for CollectingLogger {
+    fn default() -> CollectingLogger {
+        CollectingLogger::new()
+    }
+}
+
+impl Logger for CollectingLogger {
+    fn error(&self, msg: &str) {
+        self.log(&Diagnostic::new(Severity::Error, Category::Other, msg));
+    }
+
+    fn warn(&self, msg: &str) {
+        self.log(&Diagnostic::new(Severity::Warning, Category::Other, msg));
+    }
+
+    fn log(&self, diag: &Diagnostic) {
+        self.diags.borrow_mut().push(diag.clone());
+    }
+}
+
+/// How serious a diagnostic is.  Notes, warnings and errors are those of
+/// clang, and of bindgen for what it can't bind; info and debug messages
+/// tell what bindgen chose to do.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Severity {
+    Debug,
+    Info,
+    Note,
+    Warning,
+    Error,
+    Fatal,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Severity::Debug => "debug",
+            Severity::Info => "info",
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Fatal => "fatal error",
+        }.fmt(f)
+    }
+}
+
+/// A position in a source file.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// What a diagnostic is about.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Category {
+    /// Reported by clang while parsing the headers.
+    Clang,
+    /// A type with no Rust equivalent.
+    UnsupportedType,
+    /// A declaration, macro or calling convention that can't be bound as
+    /// written.
+    UnsupportedConstruct,
+    /// Declarations of different kinds sharing a name.
+    NameCollision,
+    /// A struct or union whose Rust layout differs from the C one.
+    Layout,
+    /// An option given to bindgen, as a pattern that matches nothing.
+    Options,
+    /// Anything else, as messages given to `Logger::error` and `warn`.
+    Other,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Category::Clang => "clang",
+            Category::UnsupportedType => "unsupported-type",
+            Category::UnsupportedConstruct => "unsupported-construct",
+            Category::NameCollision => "name-collision",
+            Category::Layout => "layout",
+            Category::Options => "options",
+            Category::Other => "other",
+        }.fmt(f)
+    }
+}
+
+/// A problem found by clang in the headers, by bindgen in a construct it
+/// can't bind, or a message about what bindgen did.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub category: Category,
+    pub message: String,
+    /// Where the problem is, `None` for builtin definitions and problems
+    /// with no source.
+    pub location: Option<Location>,
+}
+
+impl Diagnostic {
+    pub fn new<T: Into<String>>(severity: Severity, category: Category, message: T) -> Diagnostic {
+        Diagnostic {
+            severity: severity,
+            category: category,
+            message: message.into(),
+            location: None,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref location) = self.location {
+            try!(write!(f, "{}: ", location));
+        }
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Why bindings couldn't be generated.  The diagnostics have already been
+/// reported through the `Logger` as they were found.  libclang is linked
+/// when the crate is built rather than loaded at run time, so there is no
+/// error for it failing to load.
+#[derive(Debug)]
+pub enum Error {
+    /// Clang failed to create an index.
+    IndexCreation,
+    /// Clang produced no translation unit, as when no input file is given.
+    NoInput,
+    /// An option is invalid, as a pattern that isn't a valid regex.
+    InvalidOption(String),
+    /// Clang couldn't parse the headers.  Holds all its diagnostics,
+    /// warnings included, and is empty when clang gave no reason.
+    Parse(Vec<Diagnostic>),
+    /// The headers use constructs that can't be bound, one diagnostic for
+    /// each.  Unsupported types and calling conventions only fail when
+    /// `fail_on_unknown_type` is set, names declared as two different kinds
+    /// of declarations always do.
+    Unsupported(Vec<Diagnostic>),
+    /// A file couldn't be read.
+    Io(io::Error),
+}
+
+impl Error {
+    /// The diagnostics that caused the error, if any.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        match *self {
+            Error::Parse(ref diags) | Error::Unsupported(ref diags) => &diags[..],
+            _ => &[][..]
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::IndexCreation => "clang failed to create an index".fmt(f),
+            Error::NoInput => "no input files given".fmt(f),
+            Error::InvalidOption(ref msg) => msg.fmt(f),
+            Error::Parse(ref diags) => {
+                let errors = diags.iter().filter(|d| d.severity >= Severity::Error).count();
+                if errors == 0 {
+                    "clang failed to parse the headers".fmt(f)
+                } else {
+                    write!(f, "clang failed to parse the headers with {} error(s)", errors)
+                }
+            }
+            Error::Unsupported(ref diags) => {
+                write!(f, "the headers use {} unsupported construct(s)", diags.len())
+            }
+            Error::Io(ref e) => e.fmt(f),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::IndexCreation => "clang failed to create an index",
+            Error::NoInput => "no input files given",
+            Error::InvalidOption(ref msg) => &msg[..],
+            Error::Parse(_) => "clang failed to parse the headers",
+            Error::Unsupported(_) => "the headers use unsupported constructs",
+            Error::Io(ref e) => e.description(),
+        }
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            Error::Io(ref e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
 }
 
 #[derive(Clone)]
 pub struct Bindings {
-    module: ast::Mod
+    module: ast::Mod,
+    static_fns_shim: String,
 }
 
 impl Bindings {
     /// Deprecated - use a `Builder` instead
-    pub fn generate(options: &BindgenOptions, logger: Option<&Logger>, span: Option<Span>) -> Result<Bindings, ()> {
+    pub fn generate(options: &BindgenOptions, logger: Option<&Logger>, span: Option<Span>) -> Result<Bindings, Error> {
         let l = DummyLogger;
         let logger = match logger {
             Some(l) => l,
@@ -152,15 +590,25 @@ impl Bindings {
             None => DUMMY_SP
         };
 
-        let globals = try!(parse_headers(options, logger));
+        let mut enum_styles = vec!();
+        for &(ref pattern, style) in options.enum_styles.iter() {
+            let filter = try!(parser::NameFilter::new("enum style", &[pattern.clone()], &[])
+                                  .map_err(Error::InvalidOption));
+            enum_styles.push((filter, style));
+        }
+
+        let type_replacements = try!(parse_type_replacements(options));
+
+        let (globals, shim) = try!(parse_headers(options, logger));
 
         let module = ast::Mod {
             inner: span,
-            items: gen::gen_mod(&options.links[..], globals, span)
+            items: gen::gen_mod(options, enum_styles, type_replacements, globals, logger, span)
         };
 
         Ok(Bindings {
-            module: module
+            module: module,
+            static_fns_shim: shim,
         })
     }
 
@@ -182,6 +630,20 @@ impl Bindings {
         self.write(Box::new(file))
     }
 
+    /// The C source of the wrappers of `static` functions, and of the
+    /// function-like macros given to `wrap_macro`, which has to be compiled
+    /// and linked for the bindings to them to work.  Each wrapper is named
+    /// after what it wraps, with a `__bindgen_static` or `__bindgen_macro`
+    /// suffix.  Empty if nothing is wrapped.
+    pub fn static_fns_shim(&self) -> &str {
+        &self.static_fns_shim[..]
+    }
+
+    pub fn write_static_fns_shim<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = try!(OpenOptions::new().write(true).truncate(true).create(true).open(path));
+        file.write_all(self.static_fns_shim.as_bytes())
+    }
+
     pub fn write<'a, 'b>(&'a self, mut writer: Box<Write + 'b>) -> io::Result<()> {
         try!(writer.write("/* automatically generated by rust-bindgen */\n\n".as_bytes()));
         let mut ps = pprust::rust_printer(writer);
@@ -200,7 +662,7 @@ impl Logger for DummyLogger {
     fn warn(&self, _msg: &str) { }
 }
 
-fn parse_headers(options: &BindgenOptions, logger: &Logger) -> Result<Vec<Global>, ()> {
+fn parse_headers(options: &BindgenOptions, logger: &Logger) -> Result<(Vec<Global>, String), Error> {
     fn str_to_ikind(s: &str) -> Option<types::IKind> {
         match s {
             "uchar"     => Some(types::IUChar),
@@ -217,6 +679,22 @@ fn parse_headers(options: &BindgenOptions, logger: &Logger) -> Result<Vec<Global
         }
     }
 
+    let filters = (
+        parser::NameFilter::new("function", &options.allowlist_functions[..],
+                                &options.blocklist_functions[..]),
+        parser::NameFilter::new("type", &options.allowlist_types[..],
+                                &options.blocklist_types[..]),
+        parser::NameFilter::new("variable", &options.allowlist_vars[..],
+                                &options.blocklist_vars[..]),
+        parser::NameFilter::new("opaque type", &options.opaque_types[..], &[]),
+    );
+    let (functions, types, vars, opaque_types) = match filters {
+        (Ok(f), Ok(t), Ok(v), Ok(o)) => (f, t, v, o),
+        (Err(msg), _, _, _) | (_, Err(msg), _, _) | (_, _, Err(msg), _) | (_, _, _, Err(msg)) => {
+            return Err(Error::InvalidOption(msg));
+        }
+    };
+
     let clang_opts = parser::ClangParserOptions {
         builtin_names: builtin_names(),
         builtins: options.builtins,
@@ -225,11 +703,38 @@ fn parse_headers(options: &BindgenOptions, logger: &Logger) -> Result<Vec<Global
         fail_on_unknown_type: options.fail_on_unknown_type,
         override_enum_ty: str_to_ikind(&options.override_enum_ty[..]),
         clang_args: options.clang_args.clone(),
+        functions: functions,
+        types: types,
+        vars: vars,
+        opaque_types: opaque_types,
+        opaque_behind_pointers: options.opaque_behind_pointers,
+        replaced_types: options.type_replacements.iter().map(|&(ref c_name, _)| c_name.clone()).collect(),
+        wrap_static_fns: options.wrap_static_fns,
+        wrap_macros: options.wrap_macros.clone(),
     };
 
     parser::parse(clang_opts, logger)
 }
 
+/// Parses the Rust types given as type replacements, so that a replacement
+/// that isn't a type fails the generation before the C type is dropped.
+fn parse_type_replacements(options: &BindgenOptions) -> Result<Vec<(String, P<ast::Ty>)>, Error> {
+    let sess = parse::ParseSess::new();
+    let mut replacements = vec!();
+    for &(ref c_name, ref rust_ty) in options.type_replacements.iter() {
+        let mut parser = parse::new_parser_from_source_str(&sess, vec!(), "".to_string(),
+                                                           rust_ty.clone());
+        match parser.parse_ty_nopanic() {
+            Ok(ty) if parser.token == parse::token::Eof => replacements.push((c_name.clone(), ty)),
+            _ => {
+                let msg = format!("invalid replacement `{}` for type `{}`", rust_ty, c_name);
+                return Err(Error::InvalidOption(msg));
+            }
+        }
+    }
+    Ok(replacements)
+}
+
 fn builtin_names() -> HashSet<String> {
     let mut names = HashSet::new();
     let keys = [
@@ -261,6 +766,41 @@ fn builder_state()
     assert!(build.options.links.binary_search(&("m".to_string(), LinkType::Static)).is_ok());
 }
 
+#[test]
+fn collecting_logger() {
+    let logger = CollectingLogger::new();
+    logger.warn("plain");
+    let mut diag = Diagnostic::new(Severity::Debug, Category::Layout, "detailed");
+    diag.location = Some(Location { file: "a.h".to_string(), line: 1, column: 2 });
+    logger.log(&diag);
+    let diags = logger.into_diagnostics();
+    assert_eq!(diags.len(), 2);
+    assert_eq!(diags[0].severity, Severity::Warning);
+    assert_eq!(diags[0].category, Category::Other);
+    assert_eq!(diags[1], diag);
+    assert_eq!(diag.to_string(), "a.h:1:2: debug: detailed");
+}
+
+#[test]
+fn parse_enum_style() {
+    let mut options: BindgenOptions = Default::default();
+    assert!(options.parse_enum_style("rust").is_ok());
+    assert!(options.parse_enum_style("foo_=module").is_ok());
+    assert!(options.parse_enum_style("bar").is_err());
+    assert_eq!(options.enum_style, EnumStyle::Rust);
+    assert_eq!(options.enum_styles, vec!(("foo_".to_string(), EnumStyle::Module)));
+}
+
+#[test]
+fn parse_type_replacement() {
+    let mut options: BindgenOptions = Default::default();
+    assert!(options.parse_type_replacement("timespec=::libc::timespec").is_ok());
+    assert!(options.parse_type_replacement("=Foo").is_err());
+    assert!(options.parse_type_replacement("foo").is_err());
+    assert_eq!(options.type_replacements,
+               vec!(("timespec".to_string(), "::libc::timespec".to_string())));
+}
+
 // Get the first directory in PATH that contains a file named "clang".
 fn get_clang_dir() -> Option<path::PathBuf>{
     match env::var_os("PATH") {
@@ -0,0 +1,634 @@
+#![allow(non_upper_case_globals)]
+
+//! Evaluation of the replacement lists of object-like macros.
+//!
+//! Only constant expressions built from integer, floating point, character
+//! and string literals are understood, together with the C arithmetic,
+//! bitwise, logical and conditional operators, casts to arithmetic types
+//! and references to previously defined macros.  Anything else makes the
+//! evaluation fail and the macro is silently skipped.
+//!
+//! Integer arithmetic follows the usual arithmetic conversions of C,
+//! assuming `int` is 32 and `long` is 64 bits wide.
+
+use clang::Token;
+use clangll::*;
+use types::*;
+
+macro_rules! try_opt {
+    ($e:expr) => (match $e { Some(v) => v, None => return None })
+}
+
+#[derive(Clone, PartialEq)]
+pub enum Value {
+    /// Integers are stored sign extended from their width when signed and
+    /// zero extended otherwise, so an unsigned 64-bit value may appear
+    /// negative here.
+    Int(i64, IKind),
+    Float(f64, FKind),
+    Str(Vec<u8>),
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum NumKind {
+    Int(IKind),
+    Float(FKind),
+}
+
+pub type MacroLookup<'a> = Fn(&str) -> Option<Value> + 'a;
+pub type TypeLookup<'a> = Fn(&str) -> Option<NumKind> + 'a;
+
+/// Evaluates the tokens of a macro replacement list, not including the
+/// macro name.
+pub fn eval(tokens: &[Token], lookup: &MacroLookup, lookup_ty: &TypeLookup) -> Option<Value> {
+    if tokens.is_empty() {
+        return None;
+    }
+
+    let mut ev = Evaluator {
+        tokens: tokens,
+        pos: 0,
+        lookup: lookup,
+        lookup_ty: lookup_ty
+    };
+    let val = ev.conditional();
+    if ev.pos != tokens.len() {
+        return None;
+    }
+    val
+}
+
+fn width(kind: IKind) -> u32 {
+    match kind {
+        IBool | ISChar | IUChar => 8,
+        IShort | IUShort => 16,
+        IInt | IUInt => 32,
+        ILong | IULong | ILongLong | IULongLong => 64,
+        IInt128 | IUInt128 => 128,
+    }
+}
+
+/// Wraps `val` into the range of `kind`.
+fn truncate(val: i64, kind: IKind) -> i64 {
+    if kind == IBool {
+        return (val != 0) as i64;
+    }
+    let w = width(kind);
+    if w >= 64 {
+        return val;
+    }
+    let v = val & ((1i64 << w) - 1);
+    if kind.is_signed() && v & (1i64 << (w - 1)) != 0 {
+        v - (1i64 << w)
+    } else {
+        v
+    }
+}
+
+fn promote(kind: IKind) -> IKind {
+    if kind.rank() < IInt.rank() { IInt } else { kind }
+}
+
+fn common_kind(a: IKind, b: IKind) -> IKind {
+    let a = promote(a);
+    let b = promote(b);
+    if a == b {
+        return a;
+    }
+    if a.is_signed() == b.is_signed() {
+        return if a.rank() >= b.rank() { a } else { b };
+    }
+    let (s, u) = if a.is_signed() { (a, b) } else { (b, a) };
+    if u.rank() >= s.rank() {
+        u
+    } else if width(s) > width(u) {
+        s
+    } else {
+        s.to_unsigned()
+    }
+}
+
+fn to_f64(val: i64, kind: IKind) -> f64 {
+    if kind.is_signed() { val as f64 } else { val as u64 as f64 }
+}
+
+/// Converts `val` to the type `to`, as a C cast would.
+pub fn cast(val: Value, to: NumKind) -> Option<Value> {
+    match (val, to) {
+        // Values are evaluated as 64 bits wide
+        (_, NumKind::Int(k)) if width(k) > 64 => None,
+        (Value::Int(v, _), NumKind::Int(k)) => Some(Value::Int(truncate(v, k), k)),
+        (Value::Int(v, k), NumKind::Float(f)) => Some(Value::Float(to_f64(v, k), f)),
+        (Value::Float(v, _), NumKind::Int(k)) => {
+            let i = if k.is_signed() { v as i64 } else { v as u64 as i64 };
+            Some(Value::Int(truncate(i, k), k))
+        }
+        (Value::Float(v, _), NumKind::Float(f)) => Some(Value::Float(v, f)),
+        (Value::Str(_), _) => None,
+    }
+}
+
+fn int_binop(op: &str, a: i64, ak: IKind, b: i64, bk: IKind) -> Option<Value> {
+    if width(ak) > 64 || width(bk) > 64 {
+        return None;
+    }
+
+    // Shifts don't undergo the usual arithmetic conversions.
+    if op == "<<" || op == ">>" {
+        let k = promote(ak);
+        let a = truncate(a, k);
+        if bk.is_signed() && b < 0 || b as u64 >= width(k) as u64 {
+            return None;
+        }
+        let v = if op == "<<" {
+            a << b
+        } else if k.is_signed() {
+            a >> b
+        } else {
+            ((a as u64) >> b) as i64
+        };
+        return Some(Value::Int(truncate(v, k), k));
+    }
+
+    let k = common_kind(ak, bk);
+    let a = truncate(a, k);
+    let b = truncate(b, k);
+    let signed = k.is_signed();
+    let bool_val = |c: bool| Some(Value::Int(c as i64, IInt));
+
+    let v = match op {
+        "+" => a.wrapping_add(b),
+        "-" => a.wrapping_sub(b),
+        "*" => a.wrapping_mul(b),
+        "/" | "%" => {
+            if b == 0 {
+                return None;
+            }
+            match (op, signed) {
+                ("/", true) => match a.checked_div(b) { Some(v) => v, None => return None },
+                ("/", false) => ((a as u64) / (b as u64)) as i64,
+                (_, true) => match a.checked_rem(b) { Some(v) => v, None => return None },
+                (_, false) => ((a as u64) % (b as u64)) as i64,
+            }
+        }
+        "&" => a & b,
+        "|" => a | b,
+        "^" => a ^ b,
+        "==" => return bool_val(a == b),
+        "!=" => return bool_val(a != b),
+        "<" => return bool_val(if signed { a < b } else { (a as u64) < (b as u64) }),
+        ">" => return bool_val(if signed { a > b } else { (a as u64) > (b as u64) }),
+        "<=" => return bool_val(if signed { a <= b } else { (a as u64) <= (b as u64) }),
+        ">=" => return bool_val(if signed { a >= b } else { (a as u64) >= (b as u64) }),
+        "&&" => return bool_val(a != 0 && b != 0),
+        "||" => return bool_val(a != 0 || b != 0),
+        _ => return None,
+    };
+    Some(Value::Int(truncate(v, k), k))
+}
+
+fn float_binop(op: &str, a: f64, b: f64, kind: FKind) -> Option<Value> {
+    let bool_val = |c: bool| Some(Value::Int(c as i64, IInt));
+    let v = match op {
+        "+" => a + b,
+        "-" => a - b,
+        "*" => a * b,
+        "/" => a / b,
+        "==" => return bool_val(a == b),
+        "!=" => return bool_val(a != b),
+        "<" => return bool_val(a < b),
+        ">" => return bool_val(a > b),
+        "<=" => return bool_val(a <= b),
+        ">=" => return bool_val(a >= b),
+        "&&" => return bool_val(a != 0.0 && b != 0.0),
+        "||" => return bool_val(a != 0.0 || b != 0.0),
+        _ => return None,
+    };
+    Some(Value::Float(v, kind))
+}
+
+fn binop(op: &str, lhs: Value, rhs: Value) -> Option<Value> {
+    match (lhs, rhs) {
+        (Value::Int(a, ak), Value::Int(b, bk)) => int_binop(op, a, ak, b, bk),
+        (Value::Float(a, ak), Value::Float(b, bk)) => {
+            let k = if ak == FFloat && bk == FFloat { FFloat } else { FDouble };
+            float_binop(op, a, b, k)
+        }
+        (Value::Float(a, ak), Value::Int(b, bk)) => float_binop(op, a, to_f64(b, bk), ak),
+        (Value::Int(a, ak), Value::Float(b, bk)) => float_binop(op, to_f64(a, ak), b, bk),
+        _ => None,
+    }
+}
+
+fn is_true(val: &Value) -> Option<bool> {
+    match *val {
+        Value::Int(v, _) => Some(v != 0),
+        Value::Float(v, _) => Some(v != 0.0),
+        Value::Str(_) => None,
+    }
+}
+
+fn binop_prec(op: &str) -> Option<u32> {
+    let prec = match op {
+        "||" => 1,
+        "&&" => 2,
+        "|" => 3,
+        "^" => 4,
+        "&" => 5,
+        "==" | "!=" => 6,
+        "<" | ">" | "<=" | ">=" => 7,
+        "<<" | ">>" => 8,
+        "+" | "-" => 9,
+        "*" | "/" | "%" => 10,
+        _ => return None,
+    };
+    Some(prec)
+}
+
+struct Evaluator<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    lookup: &'a MacroLookup<'a>,
+    lookup_ty: &'a TypeLookup<'a>
+}
+
+impl<'a> Evaluator<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_punct(&self) -> Option<&'a str> {
+        match self.peek() {
+            Some(t) if t.kind == CXToken_Punctuation => Some(&t.spelling[..]),
+            _ => None,
+        }
+    }
+
+    fn eat_punct(&mut self, p: &str) -> bool {
+        if self.peek_punct() == Some(p) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn conditional(&mut self) -> Option<Value> {
+        let cond = try_opt!(self.binary(1));
+        if !self.eat_punct("?") {
+            return Some(cond);
+        }
+        let then = try_opt!(self.conditional());
+        if !self.eat_punct(":") {
+            return None;
+        }
+        let els = try_opt!(self.conditional());
+        let take_then = try_opt!(is_true(&cond));
+        // The result has the common type of both branches.
+        match (then, els) {
+            (Value::Int(a, ak), Value::Int(b, bk)) => {
+                let k = common_kind(ak, bk);
+                let v = if take_then { a } else { b };
+                Some(Value::Int(truncate(v, k), k))
+            }
+            (Value::Str(a), Value::Str(b)) => Some(Value::Str(if take_then { a } else { b })),
+            (a, b) => {
+                let (av, bv) = match (cast(a, NumKind::Float(FDouble)), cast(b, NumKind::Float(FDouble))) {
+                    (Some(Value::Float(av, _)), Some(Value::Float(bv, _))) => (av, bv),
+                    _ => return None,
+                };
+                Some(Value::Float(if take_then { av } else { bv }, FDouble))
+            }
+        }
+    }
+
+    fn binary(&mut self, min_prec: u32) -> Option<Value> {
+        let mut lhs = try_opt!(self.unary());
+        loop {
+            let op = match self.peek_punct() {
+                Some(op) => op,
+                None => return Some(lhs),
+            };
+            let prec = match binop_prec(op) {
+                Some(p) if p >= min_prec => p,
+                _ => return Some(lhs),
+            };
+            self.pos += 1;
+            let rhs = try_opt!(self.binary(prec + 1));
+            lhs = try_opt!(binop(op, lhs, rhs));
+        }
+    }
+
+    fn unary(&mut self) -> Option<Value> {
+        let op = match self.peek_punct() {
+            Some(op) => op,
+            None => return self.primary(),
+        };
+        match op {
+            "-" | "+" | "~" | "!" => {
+                self.pos += 1;
+                let val = try_opt!(self.unary());
+                match (op, val) {
+                    ("-", Value::Int(v, k)) => {
+                        let k = promote(k);
+                        Some(Value::Int(truncate(v.wrapping_neg(), k), k))
+                    }
+                    ("-", Value::Float(v, k)) => Some(Value::Float(-v, k)),
+                    ("+", Value::Int(v, k)) => Some(Value::Int(v, promote(k))),
+                    ("+", Value::Float(v, k)) => Some(Value::Float(v, k)),
+                    ("~", Value::Int(v, k)) => {
+                        let k = promote(k);
+                        Some(Value::Int(truncate(!v, k), k))
+                    }
+                    ("!", ref v) => is_true(v).map(|b| Value::Int(!b as i64, IInt)),
+                    _ => None,
+                }
+            }
+            "(" => {
+                let start = self.pos;
+                self.pos += 1;
+                if let Some(kind) = self.cast_type() {
+                    if self.eat_punct(")") {
+                        let val = try_opt!(self.unary());
+                        return cast(val, kind);
+                    }
+                }
+                self.pos = start;
+                self.primary()
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses an arithmetic type name such as `unsigned long` or a typedef
+    /// of one.
+    fn cast_type(&mut self) -> Option<NumKind> {
+        let start = self.pos;
+        if let Some(t) = self.peek() {
+            if t.kind == CXToken_Identifier {
+                if let Some(kind) = (self.lookup_ty)(&t.spelling[..]) {
+                    self.pos += 1;
+                    return Some(kind);
+                }
+                return None;
+            }
+        }
+
+        let (mut signed, mut unsigned, mut longs) = (false, false, 0);
+        let (mut char_, mut short, mut int, mut bool_) = (false, false, false, false);
+        let (mut float, mut double) = (false, false);
+        while let Some(t) = self.peek() {
+            if t.kind != CXToken_Keyword {
+                break;
+            }
+            match &t.spelling[..] {
+                "signed" => signed = true,
+                "unsigned" => unsigned = true,
+                "long" => longs += 1,
+                "char" => char_ = true,
+                "short" => short = true,
+                "int" => int = true,
+                "_Bool" | "bool" => bool_ = true,
+                "float" => float = true,
+                "double" => double = true,
+                "const" | "volatile" => (),
+                _ => break,
+            }
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return None;
+        }
+
+        let kind = if float {
+            NumKind::Float(FFloat)
+        } else if double {
+            NumKind::Float(FDouble)
+        } else if bool_ {
+            NumKind::Int(IBool)
+        } else {
+            let k = if char_ {
+                ISChar
+            } else if short {
+                IShort
+            } else if longs >= 2 {
+                ILongLong
+            } else if longs == 1 {
+                ILong
+            } else if int || signed || unsigned {
+                IInt
+            } else {
+                return None;
+            };
+            NumKind::Int(if unsigned { k.to_unsigned() } else { k })
+        };
+        Some(kind)
+    }
+
+    fn primary(&mut self) -> Option<Value> {
+        let tok = try_opt!(self.peek());
+        self.pos += 1;
+        match tok.kind {
+            CXToken_Punctuation if tok.spelling == "(" => {
+                let val = try_opt!(self.conditional());
+                if self.eat_punct(")") { Some(val) } else { None }
+            }
+            CXToken_Identifier => (self.lookup)(&tok.spelling[..]),
+            CXToken_Keyword => match &tok.spelling[..] {
+                "true" => Some(Value::Int(1, IBool)),
+                "false" => Some(Value::Int(0, IBool)),
+                _ => None,
+            },
+            CXToken_Literal => {
+                let mut val = try_opt!(parse_literal(&tok.spelling[..]));
+                // Adjacent string literals are concatenated.
+                while let Value::Str(ref mut s) = val {
+                    let next = match self.peek() {
+                        Some(t) if t.kind == CXToken_Literal => t,
+                        _ => break,
+                    };
+                    match parse_literal(&next.spelling[..]) {
+                        Some(Value::Str(more)) => {
+                            s.pop();
+                            s.extend(more.into_iter());
+                            self.pos += 1;
+                        }
+                        _ => return None,
+                    }
+                }
+                Some(val)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn parse_literal(s: &str) -> Option<Value> {
+    let body = s.trim_left_matches(|c: char| c == 'L' || c == 'u' || c == 'U' || c == '8');
+    if body.starts_with("'") {
+        if body.len() != s.len() {
+            // Wide character literals aren't supported.
+            return None;
+        }
+        parse_char(body)
+    } else if body.starts_with("\"") {
+        if body.len() != s.len() && &s[..s.len() - body.len()] != "u8" {
+            return None;
+        }
+        parse_string(body)
+    } else {
+        parse_number(s)
+    }
+}
+
+fn parse_number(s: &str) -> Option<Value> {
+    let s = s.replace("'", "");
+    let lower = s.to_lowercase();
+    let is_hex = lower.starts_with("0x");
+    if is_hex && lower.contains('p') {
+        // Hexadecimal floating point literals aren't supported.
+        return None;
+    }
+    if !is_hex && (lower.contains('.') || lower.contains('e')) {
+        return parse_float(&lower[..]);
+    }
+    parse_int(&lower[..])
+}
+
+fn parse_int(s: &str) -> Option<Value> {
+    let digits = s.trim_right_matches(|c: char| c == 'u' || c == 'l');
+    let suffix = &s[digits.len()..];
+    let unsigned = suffix.contains('u');
+    let longs = suffix.matches('l').count();
+
+    let (radix, digits) = if digits.starts_with("0x") {
+        (16, &digits[2..])
+    } else if digits.starts_with("0b") {
+        (2, &digits[2..])
+    } else if digits.len() > 1 && digits.starts_with("0") {
+        (8, &digits[1..])
+    } else {
+        (10, digits)
+    };
+    let val = match u64::from_str_radix(digits, radix) {
+        Ok(v) => v,
+        Err(_) => return None,
+    };
+
+    // Pick the first type in the list given by C11 6.4.4.1 that can
+    // represent the value.  `long` is skipped for unsuffixed and `u`
+    // suffixed literals in favour of the portable `long long`.
+    let candidates = match (unsigned, longs, radix == 10) {
+        (false, 0, true) => vec!(IInt, ILongLong),
+        (false, 0, false) => vec!(IInt, IUInt, ILongLong, IULongLong),
+        (true, 0, _) => vec!(IUInt, IULongLong),
+        (false, 1, true) => vec!(ILong, ILongLong),
+        (false, 1, false) => vec!(ILong, IULong, ILongLong, IULongLong),
+        (true, 1, _) => vec!(IULong, IULongLong),
+        (false, _, true) => vec!(ILongLong),
+        (false, _, false) => vec!(ILongLong, IULongLong),
+        (true, _, _) => vec!(IULongLong),
+    };
+    for &k in candidates.iter() {
+        let max = if k.is_signed() {
+            (1u64 << (width(k) - 1)) - 1
+        } else if width(k) == 64 {
+            !0u64
+        } else {
+            (1u64 << width(k)) - 1
+        };
+        if val <= max {
+            return Some(Value::Int(val as i64, k));
+        }
+    }
+    None
+}
+
+fn parse_float(s: &str) -> Option<Value> {
+    let (digits, kind) = if s.ends_with("f") {
+        (&s[..s.len() - 1], FFloat)
+    } else if s.ends_with("l") {
+        (&s[..s.len() - 1], FDouble)
+    } else {
+        (s, FDouble)
+    };
+    match digits.parse::<f64>() {
+        Ok(v) => Some(Value::Float(v, kind)),
+        Err(_) => None,
+    }
+}
+
+fn parse_char(s: &str) -> Option<Value> {
+    if s.len() < 3 || !s.ends_with("'") {
+        return None;
+    }
+    let bytes = try_opt!(unescape(&s[1..s.len() - 1]));
+    if bytes.len() != 1 {
+        return None;
+    }
+    let c = bytes[0];
+    if c > 127 {
+        Some(Value::Int(c as i64, IUChar))
+    } else {
+        Some(Value::Int(c as i64, ISChar))
+    }
+}
+
+/// Parses a string literal into its bytes, including the terminating nul.
+fn parse_string(s: &str) -> Option<Value> {
+    if s.len() < 2 || !s.ends_with("\"") {
+        return None;
+    }
+    let mut bytes = try_opt!(unescape(&s[1..s.len() - 1]));
+    bytes.push(0);
+    Some(Value::Str(bytes))
+}
+
+fn unescape(s: &str) -> Option<Vec<u8>> {
+    let bytes = s.as_bytes();
+    let mut out = vec!();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        i += 1;
+        if b != b'\\' {
+            out.push(b);
+            continue;
+        }
+        let e = try_opt!(bytes.get(i).cloned());
+        i += 1;
+        let c = match e {
+            b'n' => b'\n',
+            b't' => b'\t',
+            b'r' => b'\r',
+            b'a' => 7,
+            b'b' => 8,
+            b'f' => 12,
+            b'v' => 11,
+            b'\\' | b'\'' | b'"' | b'?' => e,
+            b'x' => {
+                let start = i;
+                while i < bytes.len() && (bytes[i] as char).is_digit(16) {
+                    i += 1;
+                }
+                match u8::from_str_radix(&s[start..i], 16) {
+                    Ok(v) => v,
+                    Err(_) => return None,
+                }
+            }
+            b'0'...b'7' => {
+                let start = i - 1;
+                while i < bytes.len() && i < start + 3 && (bytes[i] as char).is_digit(8) {
+                    i += 1;
+                }
+                match u8::from_str_radix(&s[start..i], 8) {
+                    Ok(v) => v,
+                    Err(_) => return None,
+                }
+            }
+            _ => return None,
+        };
+        out.push(c);
+    }
+    Some(out)
+}
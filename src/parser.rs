@@ -2,19 +2,26 @@
 
 use std::collections::{HashMap, HashSet};
 use std::collections::hash_map;
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::cell::RefCell;
 use std::ops::Deref;
 use std::rc::Rc;
 
+use regex::Regex;
 use syntax::abi;
 
 use types as il;
 use types::*;
 use clang as cx;
-use clang::{ast_dump, Cursor, Diagnostic, TranslationUnit, type_to_str};
+use clang::{ast_dump, call_conv_to_str, Comment, Cursor, SourceLocation, TranslationUnit,
+            UnsavedFile, type_to_str};
 use clangll::*;
+use macros;
 
-use super::Logger;
+use super::{Category, Diagnostic, Error, Location, Logger, Severity};
 
 pub struct ClangParserOptions {
     pub builtin_names: HashSet<String>,
@@ -24,15 +31,115 @@ pub struct ClangParserOptions {
     pub fail_on_unknown_type: bool,
     pub override_enum_ty: Option<il::IKind>,
     pub clang_args: Vec<String>,
+    pub functions: NameFilter,
+    pub types: NameFilter,
+    pub vars: NameFilter,
+    pub opaque_behind_pointers: bool,
+    pub opaque_types: NameFilter,
+    /// Types the user provides Rust definitions for.
+    pub replaced_types: HashSet<String>,
+    /// Binds `static` functions through exported wrappers, whose C source
+    /// is returned along with the globals.
+    pub wrap_static_fns: bool,
+    /// Prototypes of function-like macros to bind through wrappers too.
+    pub wrap_macros: Vec<String>,
+}
+
+/// Appended to the name of a `static` function to name its wrapper.
+pub const STATIC_FN_SUFFIX: &'static str = "__bindgen_static";
+/// Appended to the name of a function-like macro to name its wrapper.
+pub const MACRO_SUFFIX: &'static str = "__bindgen_macro";
+
+/// Regex allow and block lists for the names of one kind of declaration.
+/// Patterns have to match the whole name.
+pub struct NameFilter {
+    what: &'static str,
+    allow: Vec<(String, Regex, bool)>,
+    block: Vec<(String, Regex, bool)>,
+}
+
+impl NameFilter {
+    pub fn new(what: &'static str, allow: &[String], block: &[String]) -> Result<NameFilter, String> {
+        fn compile(what: &str, pats: &[String]) -> Result<Vec<(String, Regex, bool)>, String> {
+            let mut res = vec!();
+            for pat in pats.iter() {
+                match Regex::new(&format!("^(?:{})$", pat)[..]) {
+                    Ok(re) => res.push((pat.clone(), re, false)),
+                    Err(e) => return Err(format!("invalid {} pattern `{}`: {}", what, pat, e))
+                }
+            }
+            Ok(res)
+        }
+
+        Ok(NameFilter {
+            what: what,
+            allow: try!(compile(what, allow)),
+            block: try!(compile(what, block)),
+        })
+    }
+
+    pub fn has_allowlist(&self) -> bool {
+        !self.allow.is_empty()
+    }
+
+    /// Whether a declaration named `name` should be bound.  Every pattern
+    /// matching it is recorded as used.
+    fn allows(&mut self, name: &str) -> bool {
+        let allowlisted = self.allowlists(name);
+        let blocked = self.blocks(name);
+        (allowlisted || !self.has_allowlist()) && !blocked
+    }
+
+    pub fn allowlists(&mut self, name: &str) -> bool {
+        NameFilter::matches(&mut self.allow, name)
+    }
+
+    fn blocks(&mut self, name: &str) -> bool {
+        NameFilter::matches(&mut self.block, name)
+    }
+
+    fn matches(pats: &mut [(String, Regex, bool)], name: &str) -> bool {
+        let mut found = false;
+        for &mut (_, ref re, ref mut used) in pats.iter_mut() {
+            if re.is_match(name) {
+                *used = true;
+                found = true;
+            }
+        }
+        found
+    }
+
+    pub fn report_unused(&self, logger: &Logger) {
+        for &(ref pat, _, used) in self.allow.iter().chain(self.block.iter()) {
+            if !used {
+                let msg = format!("{} pattern `{}` did not match anything", self.what, pat);
+                logger.log(&Diagnostic::new(Severity::Warning, Category::Options, msg));
+            }
+        }
+    }
 }
 
 struct ClangParserCtx<'a> {
     options: ClangParserOptions,
     name: HashMap<Cursor, Global>,
     globals: Vec<Global>,
+    /// Types that aren't bound on their own when filtering by name, only if
+    /// a bound declaration needs them.
+    deps: Vec<Global>,
     builtin_defs: Vec<Cursor>,
+    /// Values of all object-like macros evaluated so far, including those
+    /// from files that aren't bound, so that later macros may refer to them.
+    /// Constant variables with a known value are recorded here too.
+    macros: HashMap<String, macros::Value>,
+    /// Arithmetic typedefs usable in casts inside macro definitions.
+    typedef_kinds: HashMap<String, macros::NumKind>,
+    /// Definitions of the wrappers of `static` functions and macros.
+    wrappers: Vec<String>,
+    /// The function-like macros wrappers are declared for.
+    wrapped_macros: HashSet<String>,
     logger: &'a (Logger+'a),
-    err_count: i32
+    /// The errors reported about constructs that can't be bound.
+    errors: Vec<Diagnostic>
 }
 
 fn match_pattern(ctx: &mut ClangParserCtx, cursor: &Cursor) -> bool {
@@ -71,7 +178,7 @@ fn decl_name(ctx: &mut ClangParserCtx, cursor: &Cursor) -> Global {
             let layout = Layout::new(ty.size(), ty.align());
 
             let glob_decl = match cursor.kind() {
-                CXCursor_StructDecl => {
+                CXCursor_StructDecl | CXCursor_ClassDecl => {
                     let ci = Rc::new(RefCell::new(CompInfo::new(spelling, CompKind::Struct, vec!(), layout)));
                     GCompDecl(ci)
                 }
@@ -114,6 +221,15 @@ fn decl_name(ctx: &mut ClangParserCtx, cursor: &Cursor) -> Global {
                 _ => GOther,
             };
 
+            let namespace = cursor_namespace(&cursor);
+            match glob_decl {
+                GCompDecl(ref ci) => ci.borrow_mut().namespace = namespace,
+                GEnumDecl(ref ei) => ei.borrow_mut().namespace = namespace,
+                GType(ref ti) => ti.borrow_mut().namespace = namespace,
+                GVar(ref vi) | GFunc(ref vi) => vi.borrow_mut().namespace = namespace,
+                _ => ()
+            }
+
             e.insert(glob_decl.clone());
             glob_decl
         },
@@ -128,6 +244,44 @@ fn decl_name(ctx: &mut ClangParserCtx, cursor: &Cursor) -> Global {
     return decl;
 }
 
+/// The names of the C++ namespaces enclosing `cursor`, outermost first.
+/// Anonymous namespaces are left out, nothing in them can be linked to.
+fn cursor_namespace(cursor: &Cursor) -> Vec<String> {
+    let mut namespace = vec!();
+    let mut parent = cursor.semantic_parent();
+    loop {
+        match parent.kind() {
+            CXCursor_Namespace => {
+                let name = parent.spelling();
+                if !name.is_empty() {
+                    namespace.insert(0, name);
+                }
+            }
+            CXCursor_TranslationUnit => break,
+            k if k >= CXCursor_FirstInvalid && k <= CXCursor_LastInvalid => break,
+            _ => ()
+        }
+        parent = parent.semantic_parent();
+    }
+    namespace
+}
+
+/// The symbol of a function or variable, when it isn't just its name as
+/// for C++ declarations outside of `extern "C"`.
+fn mangled_name(cursor: &Cursor) -> Option<String> {
+    let name = cursor.spelling();
+    let mut mangled = cursor.mangling();
+    // Darwin prefixes every symbol with an underscore, which rustc adds back.
+    if mangled.starts_with("__Z") || mangled == format!("_{}", name) {
+        mangled.remove(0);
+    }
+    if mangled.is_empty() || mangled == name {
+        None
+    } else {
+        Some(mangled)
+    }
+}
+
 fn opaque_decl(ctx: &mut ClangParserCtx, decl: &Cursor) {
     let name = decl_name(ctx, decl);
     ctx.globals.push(name);
@@ -143,16 +297,45 @@ fn fwd_decl<F:FnOnce(&mut ClangParserCtx)->()>(ctx: &mut ClangParserCtx, cursor:
     }
 }
 
-fn get_abi(cc: Enum_CXCallingConv) -> abi::Abi {
+/// The Rust ABI of a calling convention.  `sysv64` is the C ABI of x86_64
+/// targets other than Windows, and the VFP variant of AAPCS the one of
+/// hard-float ARM targets, elsewhere they give `None`.  The syntax crate has
+/// no ABI for `thiscall` and `vectorcall`, which give `None` like the rest.
+fn get_abi(ctx: &ClangParserCtx, cc: Enum_CXCallingConv) -> Option<abi::Abi> {
     match cc {
-        CXCallingConv_Default => abi::C,
-        CXCallingConv_C => abi::C,
-        CXCallingConv_X86StdCall => abi::Stdcall,
-        CXCallingConv_X86FastCall => abi::Fastcall,
-        CXCallingConv_AAPCS => abi::Aapcs,
-        CXCallingConv_X86_64Win64 => abi::Win64,
-        _other => panic!("unsupported calling convention: {}", _other),
+        CXCallingConv_Default => Some(abi::C),
+        CXCallingConv_C => Some(abi::C),
+        CXCallingConv_X86StdCall => Some(abi::Stdcall),
+        CXCallingConv_X86FastCall => Some(abi::Fastcall),
+        CXCallingConv_AAPCS => Some(abi::Aapcs),
+        CXCallingConv_X86_64Win64 => Some(abi::Win64),
+        CXCallingConv_X86_64SysV => {
+            let target = target_triple(&ctx.options.clang_args[..]);
+            let windows = ["windows", "win32", "mingw", "cygwin"].iter().any(|os| target.contains(os));
+            if target.starts_with("x86_64") && !windows { Some(abi::C) } else { None }
+        }
+        CXCallingConv_AAPCS_VFP => {
+            let target = target_triple(&ctx.options.clang_args[..]);
+            let arm = target.starts_with("arm") || target.starts_with("thumb");
+            if arm && target.ends_with("hf") { Some(abi::C) } else { None }
+        }
+        _ => None,
+    }
+}
+
+/// The target clang compiles for, as given with `-target` or `--target=`,
+/// else the architecture and OS of the host, which tell nothing about its
+/// float ABI.
+fn target_triple(args: &[String]) -> String {
+    let mut target = None;
+    for (i, arg) in args.iter().enumerate() {
+        if arg == "-target" {
+            target = args.get(i + 1).cloned();
+        } else if arg.starts_with("--target=") {
+            target = Some(arg["--target=".len()..].to_string());
+        }
     }
+    target.unwrap_or(format!("{}-{}", env::consts::ARCH, env::consts::OS))
 }
 
 fn conv_ptr_ty(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor, layout: Layout) -> il::Type {
@@ -167,7 +350,10 @@ fn conv_ptr_ty(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor, layout:
             let ret_ty = ty.ret_type();
             let decl = ty.declaration();
             return if ret_ty.kind() != CXType_Invalid {
-                TFuncPtr(mk_fn_sig(ctx, ty, cursor))
+                match mk_fn_sig(ctx, ty, cursor) {
+                    Some(sig) => TFuncPtr(sig),
+                    None => TPtr(Box::new(TVoid), is_const, layout)
+                }
             } else if decl.kind() != CXCursor_NoDeclFound {
                 TPtr(Box::new(conv_decl_ty(ctx, &decl)), ty.is_const(), layout)
             } else if cursor.kind() == CXCursor_VarDecl {
@@ -191,9 +377,27 @@ fn conv_ptr_ty(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor, layout:
     }
 }
 
-fn mk_fn_sig(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor) -> il::FuncSig {
+/// Converts the signature of a function type.  Functions with a calling
+/// convention Rust can't call are reported, and give `None`.
+fn mk_fn_sig(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor) -> Option<il::FuncSig> {
+    let abi = match get_abi(ctx, ty.call_conv()) {
+        Some(abi) => abi,
+        None => {
+            let fail = ctx.options.fail_on_unknown_type;
+            log_err_warn(ctx, Category::UnsupportedConstruct,
+                &format!("unsupported calling convention `{}` of `{}`",
+                    call_conv_to_str(ty.call_conv()), cursor.spelling()
+                )[..],
+                &cursor.location(),
+                fail
+            );
+            return None;
+        }
+    };
+
     let args_lst: Vec<(String, il::Type)> = match cursor.kind() {
-        CXCursor_FunctionDecl => {
+        CXCursor_FunctionDecl | CXCursor_CXXMethod |
+        CXCursor_Constructor | CXCursor_Destructor => {
             // For CXCursor_FunctionDecl, cursor.args() is the reliable way to
             // get parameter names and types.
             cursor.args().iter().map(|arg| {
@@ -216,37 +420,227 @@ fn mk_fn_sig(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor) -> il::Fu
     };
 
     let ret_ty = Box::new(conv_ty(ctx, &ty.ret_type(), cursor));
-    let abi = get_abi(ty.call_conv());
 
-    il::FuncSig {
+    // GNU `noreturn` is part of the function type, `_Noreturn` and
+    // `[[noreturn]]` are attributes of the declaration.
+    let noreturn = is_noreturn_fn_ty(ty) || match cursor.kind() {
+        CXCursor_FunctionDecl | CXCursor_CXXMethod => {
+            has_attr(&decl_attrs(cursor), &["noreturn", "Noreturn"])
+        }
+        _ => false
+    };
+
+    Some(il::FuncSig {
         ret_ty: ret_ty,
         args: args_lst,
         is_variadic: ty.is_variadic(),
         abi: abi,
+        noreturn: noreturn,
+    })
+}
+
+/// Whether a function type has the GNU `noreturn` attribute, which clang
+/// spells after the parameter list.  Attributes of the parameter types are
+/// inside the list and don't count.
+fn is_noreturn_fn_ty(ty: &cx::Type) -> bool {
+    let spelling = ty.spelling();
+    let mut rest = spelling.trim_right();
+    while rest.ends_with(")") {
+        let start = match rest.rfind("__attribute__((") {
+            Some(start) => start,
+            None => return false
+        };
+        // The closing parenthesis of a parameter list is left over after
+        // an attribute of the last parameter.
+        let attr = &rest[start..];
+        if attr.matches('(').count() != attr.matches(')').count() {
+            return false;
+        }
+        if attr == "__attribute__((noreturn))" {
+            return true;
+        }
+        rest = rest[..start].trim_right();
+    }
+    false
+}
+
+/// The attributes of the declaration `cursor`, each as its cursor kind and
+/// the spelling of its tokens, starting with the name of the attribute.
+fn decl_attrs(cursor: &Cursor) -> Vec<(Enum_CXCursorKind, Vec<String>)> {
+    let mut attrs = vec!();
+    cursor.visit(|c: &Cursor, _: &Cursor| {
+        if c.kind() >= CXCursor_FirstAttr && c.kind() <= CXCursor_LastAttr {
+            let tokens = c.translation_unit().tokens(c).unwrap_or(vec!());
+            attrs.push((c.kind(), tokens.into_iter().map(|t| t.spelling).collect()));
+        }
+        CXChildVisit_Continue
+    });
+    attrs
+}
+
+/// Whether one of `attrs` is spelled with one of `names`, with or without
+/// the surrounding double underscores.
+fn has_attr(attrs: &[(Enum_CXCursorKind, Vec<String>)], names: &[&str]) -> bool {
+    attrs.iter().any(|&(_, ref tokens)| {
+        tokens.first().map_or(false, |t| names.contains(&t.trim_matches('_')))
+    })
+}
+
+/// Whether the declaration has `visibility("hidden")`, making its symbol
+/// unavailable outside of the library defining it.
+fn is_hidden(attrs: &[(Enum_CXCursorKind, Vec<String>)]) -> bool {
+    attrs.iter().any(|&(_, ref tokens)| {
+        tokens.first().map_or(false, |t| t.trim_matches('_') == "visibility") &&
+        tokens.iter().any(|t| t == "\"hidden\"")
+    })
+}
+
+/// The message of the `deprecated` attribute of `cursor`, if it has one.
+fn deprecation(cursor: &Cursor) -> Option<String> {
+    if cursor.is_deprecated() {
+        Some(cursor.deprecated_message())
+    } else {
+        None
+    }
+}
+
+/// Renders the inline content of a paragraph comment, one line per line of
+/// the original comment.
+fn comment_text(para: &Comment) -> String {
+    let mut lines = vec!();
+    let mut line = String::new();
+    for c in para.children().iter() {
+        match c.kind() {
+            CXComment_Text => line.push_str(&c.text()[..]),
+            CXComment_InlineCommand => {
+                let args = c.inline_args().connect(" ");
+                match c.inline_render_kind() {
+                    CXCommentInlineCommandRenderKind_Bold => line.push_str(&format!("**{}**", args)[..]),
+                    CXCommentInlineCommandRenderKind_Monospaced => line.push_str(&format!("`{}`", args)[..]),
+                    CXCommentInlineCommandRenderKind_Emphasized => line.push_str(&format!("*{}*", args)[..]),
+                    _ => line.push_str(&args[..]),
+                }
+            }
+            CXComment_HTMLStartTag | CXComment_HTMLEndTag => line.push_str(&c.html_string()[..]),
+            _ => (),
+        }
+        if c.has_trailing_newline() {
+            lines.push(line.trim().to_string());
+            line.clear();
+        }
+    }
+    lines.push(line.trim().to_string());
+    lines.retain(|l| !l.is_empty());
+    lines.connect("\n")
+}
+
+/// Strips the comment markers from a comment that libclang could not parse.
+fn strip_raw_comment(raw: &str) -> String {
+    let lines: Vec<&str> = raw.lines().map(|l| {
+        let l = l.trim();
+        let l = if l.starts_with("/**") || l.starts_with("/*!") || l.starts_with("///") || l.starts_with("//!") {
+            &l[3..]
+        } else if l.starts_with("/*") || l.starts_with("//") {
+            &l[2..]
+        } else {
+            l
+        };
+        let l = if l.ends_with("*/") { &l[..l.len() - 2] } else { l };
+        let l = if l.starts_with("<") { &l[1..] } else { l };
+        l.trim_left_matches('*').trim()
+    }).collect();
+    let start = lines.iter().position(|l| !l.is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|l| !l.is_empty()).map(|e| e + 1).unwrap_or(start);
+    lines[start..end].connect("\n")
+}
+
+/// Converts the documentation comment attached to a declaration into
+/// markdown, turning `@param` and `@return` blocks into "Arguments" and
+/// "Returns" sections.
+fn doc_comment(cursor: &Cursor) -> String {
+    let comment = cursor.comment();
+    if comment.kind() != CXComment_FullComment {
+        return strip_raw_comment(&cursor.raw_comment()[..]);
+    }
+
+    let mut paragraphs = vec!();
+    let mut params = vec!();
+    let mut returns = vec!();
+    for c in comment.children().iter() {
+        if c.is_whitespace() {
+            continue;
+        }
+        match c.kind() {
+            CXComment_Paragraph => paragraphs.push(comment_text(c)),
+            CXComment_ParamCommand => {
+                let text = comment_text(&c.block_paragraph());
+                params.push(format!("* `{}` - {}", c.param_name(), text));
+            }
+            CXComment_BlockCommand => {
+                let text = comment_text(&c.block_paragraph());
+                match &c.block_command_name()[..] {
+                    "return" | "returns" | "result" => returns.push(text),
+                    "brief" | "short" | "details" => paragraphs.push(text),
+                    name => {
+                        let mut title = name[..1].to_uppercase();
+                        title.push_str(&name[1..]);
+                        paragraphs.push(format!("**{}:** {}", title, text));
+                    }
+                }
+            }
+            CXComment_VerbatimBlockCommand => {
+                let lines: Vec<String> = c.children().iter()
+                    .filter(|l| l.kind() == CXComment_VerbatimBlockLine)
+                    .map(|l| l.verbatim_block_line_text())
+                    .collect();
+                paragraphs.push(format!("```text\n{}\n```", lines.connect("\n")));
+            }
+            CXComment_VerbatimLine => paragraphs.push(c.verbatim_line_text().trim().to_string()),
+            _ => (),
+        }
     }
+
+    let mut sections: Vec<String> = paragraphs.into_iter().filter(|p| !p.is_empty()).collect();
+    if !params.is_empty() {
+        sections.push(format!("# Arguments\n\n{}", params.connect("\n")));
+    }
+    if !returns.is_empty() {
+        sections.push(format!("# Returns\n\n{}", returns.connect("\n\n")));
+    }
+    sections.connect("\n\n")
 }
 
 fn conv_decl_ty(ctx: &mut ClangParserCtx, cursor: &Cursor) -> il::Type {
     return match cursor.kind() {
-        CXCursor_StructDecl => {
-            let decl = decl_name(ctx, cursor);
-            let ci = decl.compinfo();
-            TComp(ci)
-        }
-        CXCursor_UnionDecl => {
+        CXCursor_StructDecl | CXCursor_ClassDecl | CXCursor_UnionDecl => {
             let decl = decl_name(ctx, cursor);
-            let ci = decl.compinfo();
-            TComp(ci)
+            match decl.compinfo() {
+                Some(ci) => TComp(ci),
+                None => {
+                    kind_mismatch(ctx, cursor, &decl, "struct or union");
+                    TVoid
+                }
+            }
         }
         CXCursor_EnumDecl => {
             let decl = decl_name(ctx, cursor);
-            let ei = decl.enuminfo();
-            TEnum(ei)
+            match decl.enuminfo() {
+                Some(ei) => TEnum(ei),
+                None => {
+                    kind_mismatch(ctx, cursor, &decl, "enum");
+                    TVoid
+                }
+            }
         }
         CXCursor_TypedefDecl => {
             let decl = decl_name(ctx, cursor);
-            let ti = decl.typeinfo();
-            TNamed(ti)
+            match decl.typeinfo() {
+                Some(ti) => TNamed(ti),
+                None => {
+                    kind_mismatch(ctx, cursor, &decl, "typedef");
+                    TVoid
+                }
+            }
         }
         _ => TVoid
     };
@@ -270,32 +664,51 @@ fn conv_ty(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor) -> il::Type
         CXType_Int => TInt(IInt, layout),
         CXType_Long => TInt(ILong, layout),
         CXType_LongLong => TInt(ILongLong, layout),
+        CXType_Int128 => TInt(IInt128, layout),
+        CXType_UInt128 => TInt(IUInt128, layout),
         CXType_Float => TFloat(FFloat, layout),
         CXType_Double => TFloat(FDouble, layout),
-        CXType_LongDouble => TFloat(FDouble, layout),
-        CXType_Pointer => conv_ptr_ty(ctx, &ty.pointee_type(), cursor, layout),
+        CXType_LongDouble => TFloat(FLongDouble, layout),
+        CXType_Complex => match ty.complex_elem_type().kind() {
+            CXType_Float => TFloat(FComplexFloat, layout),
+            CXType_Double => TFloat(FComplexDouble, layout),
+            CXType_LongDouble => TFloat(FComplexLongDouble, layout),
+            _ => unsupported_ty(ctx, ty, cursor)
+        },
+        CXType_Pointer |
+        CXType_LValueReference |
+        CXType_RValueReference => conv_ptr_ty(ctx, &ty.pointee_type(), cursor, layout),
         CXType_VariableArray | CXType_DependentSizedArray | CXType_IncompleteArray => {
             conv_ptr_ty(ctx, &ty.elem_type(), cursor, layout)
         }
-        CXType_FunctionProto => TFuncProto(mk_fn_sig(ctx, ty, cursor)),
+        CXType_FunctionProto => match mk_fn_sig(ctx, ty, cursor) {
+            Some(sig) => TFuncProto(sig),
+            None => TVoid
+        },
+        CXType_ObjCId | CXType_ObjCObjectPointer => TObjC(OId, layout),
+        CXType_ObjCClass => TObjC(OClass, layout),
+        CXType_ObjCSel => TObjC(OSel, layout),
+        // Only declared implicitly, as a typedef of `id`
+        CXType_Typedef if ty.declaration().spelling() == "instancetype" => TObjC(OId, layout),
         CXType_Record |
         CXType_Typedef  |
         CXType_Unexposed |
         CXType_Enum => conv_decl_ty(ctx, &ty.declaration()),
         CXType_ConstantArray => TArray(Box::new(conv_ty(ctx, &ty.elem_type(), cursor)), ty.array_size(), layout),
-        _ => {
-            let fail = ctx.options.fail_on_unknown_type;
-            log_err_warn(ctx,
-                &format!("unsupported type `{}` ({})",
-                    type_to_str(ty.kind()), cursor.location()
-                )[..],
-                fail
-            );
-            TVoid
-        },
+        _ => unsupported_ty(ctx, ty, cursor),
     };
 }
 
+fn unsupported_ty(ctx: &mut ClangParserCtx, ty: &cx::Type, cursor: &Cursor) -> il::Type {
+    let fail = ctx.options.fail_on_unknown_type;
+    log_err_warn(ctx, Category::UnsupportedType,
+        &format!("unsupported type `{}`", type_to_str(ty.kind()))[..],
+        &cursor.location(),
+        fail
+    );
+    TVoid
+}
+
 fn opaque_ty(ctx: &mut ClangParserCtx, ty: &cx::Type) {
     if ty.kind() == CXType_Record || ty.kind() == CXType_Enum {
         let decl = ty.declaration();
@@ -314,12 +727,38 @@ fn visit_composite(cursor: &Cursor, parent: &Cursor,
                    ctx: &mut ClangParserCtx,
                    members: &mut Vec<CompMember>) -> Enum_CXVisitorResult {
 
-    fn is_bitfield_continuation(field: &il::FieldInfo, ty: &il::Type, width: u32) -> bool {
+    /// The offset within the storage unit of `field` of a bitfield at bit
+    /// `offset` of the record, if the bitfield fits in the unit.
+    fn bitfield_continuation(field: &il::FieldInfo, ty: &il::Type, offset: Option<usize>,
+                             width: u32) -> Option<u32> {
         match (&field.bitfields, ty) {
             (&Some(ref bitfields), &il::TInt(_, layout)) if *ty == field.ty => {
-                bitfields.iter().map(|&(_, w)| w).fold(0u32, |acc, w| acc + w) + width <= (layout.size * 8) as u32
+                let in_unit = match (offset, field.offset) {
+                    (Some(offset), Some(start)) if offset >= start => offset - start,
+                    (Some(_), Some(_)) => return None,
+                    _ => bitfields.last().map_or(0, |&(_, w, o)| o + w) as usize
+                };
+                if in_unit + width as usize <= layout.size * 8 {
+                    Some(in_unit as u32)
+                } else {
+                    None
+                }
             },
-            _ => false
+            _ => None
+        }
+    }
+
+    /// The integer type holding the storage unit of a bitfield of type `ty`.
+    fn bitfield_ty(ty: &il::Type) -> Option<il::Type> {
+        match *ty {
+            il::TInt(IBool, layout) => Some(il::TInt(IUChar, layout)),
+            il::TInt(_, _) => Some(ty.clone()),
+            il::TEnum(ref ei) => {
+                let ei = ei.borrow();
+                Some(il::TInt(ei.kind, ei.layout))
+            }
+            il::TNamed(ref ti) => bitfield_ty(&ti.borrow().ty),
+            _ => None
         }
     }
 
@@ -327,32 +766,48 @@ fn visit_composite(cursor: &Cursor, parent: &Cursor,
         CXCursor_FieldDecl => {
             let ty = conv_ty(ctx, &cursor.cur_type(), cursor);
 
-            let (name, bitfields) = match (cursor.bit_width(), members.last_mut()) {
-                // The field is a continuation of an exising bitfield
-                (Some(width), Some(&mut il::CompMember::Field(ref mut field)))
-                    if is_bitfield_continuation(field, &ty, width) => {
+            // Bitfields are stored in units of their declared type, the
+            // offset of a group of them is the one of the unit holding the
+            // first bitfield.
+            let (name, ty, bitfields, unit_offset) = match cursor.bit_width() {
+                Some(width) => {
+                    let bit_offset = cursor.offset_of_field();
+                    let unit_ty = match bitfield_ty(&ty) {
+                        Some(unit_ty) => unit_ty,
+                        None => {
+                            let msg = format!("bitfield `{}` of `{}` is not of an integer type, \
+                                               it has no accessors", cursor.spelling(), parent.spelling());
+                            report(ctx, Severity::Warning, Category::UnsupportedType, &msg[..],
+                                   &cursor.location());
+                            ty.clone()
+                        }
+                    };
 
-                    if let Some(ref mut bitfields) = field.bitfields {
-                        bitfields.push((cursor.spelling(), width));
-                    } else { unreachable!() }
-                    return CXChildVisit_Continue;
-                },
-                // The field is the start of a new bitfield
-                (Some(width), _) => {
-                    // Bitfields containing enums are not supported by the c standard
-                    // https://stackoverflow.com/questions/11983231/is-it-safe-to-use-an-enum-in-a-bit-field
-                    match &ty {
-                        &il::TInt(_, _) => (),
-                        _ => {
-                            let msg = format!("Enums in bitfields are not supported ({}.{}).",
-                                cursor.spelling(), parent.spelling());
-                            ctx.logger.warn(&msg[..]);
+                    // The field is a continuation of an exising bitfield
+                    if let Some(&mut il::CompMember::Field(ref mut field)) = members.last_mut() {
+                        if let Some(in_unit) = bitfield_continuation(field, &unit_ty, bit_offset, width) {
+                            if let Some(ref mut bitfields) = field.bitfields {
+                                bitfields.push((cursor.spelling(), width, in_unit));
+                            }
+                            return CXChildVisit_Continue;
                         }
                     }
-                    ("".to_string(), Some(vec!((cursor.spelling(), width))))
+
+                    // The field is the start of a new bitfield
+                    let unit_bits = unit_ty.size() * 8;
+                    let (unit_offset, in_unit) = match bit_offset {
+                        Some(offset) if unit_bits > 0 => {
+                            (Some(offset - offset % unit_bits), (offset % unit_bits) as u32)
+                        }
+                        offset => (offset, 0)
+                    };
+                    ("".to_string(), unit_ty, Some(vec!((cursor.spelling(), width, in_unit))), unit_offset)
                 },
                 // The field is not a bitfield
-                (None, _) => (cursor.spelling(), None)
+                None => {
+                    let offset = parent.cur_type().offset_of(&cursor.spelling()[..]);
+                    (cursor.spelling(), ty, None, offset)
+                }
             };
 
             // The Clang C api does not fully expose composite fields, but it
@@ -401,7 +856,9 @@ fn visit_composite(cursor: &Cursor, parent: &Cursor,
                 _ => false
             };
 
-            let field = FieldInfo::new(name, ty.clone(), bitfields);
+            let mut field = FieldInfo::new(name, ty.clone(), bitfields);
+            field.offset = unit_offset;
+            field.comment = doc_comment(cursor);
             if is_composite {
                 if let Some(CompMember::Comp(c)) = members.pop() {
                     members.push(CompMember::CompField(c, field));
@@ -412,27 +869,32 @@ fn visit_composite(cursor: &Cursor, parent: &Cursor,
                 members.push(CompMember::Field(field));
             }
         }
-        CXCursor_StructDecl | CXCursor_UnionDecl => {
+        CXCursor_StructDecl | CXCursor_UnionDecl | CXCursor_ClassDecl => {
             fwd_decl(ctx, cursor, |ctx_| {
                 // If the struct is anonymous (i.e. declared here) then it
                 // cannot be used elsewhere and so does not need to be added
                 // to globals otherwise it will be declared later and a global.
                 let decl = decl_name(ctx_, cursor);
-                let ci = decl.compinfo();
+                let ci = match decl.compinfo() {
+                    Some(ci) => ci,
+                    None => return kind_mismatch(ctx_, cursor, &decl, "struct or union")
+                };
+                ci.borrow_mut().comment = doc_comment(cursor);
                 cursor.visit(|c, p| {
                     let mut ci_ = ci.borrow_mut();
                     visit_composite(c, p, ctx_, &mut ci_.members)
                 });
-                members.push(CompMember::Comp(decl.compinfo()));
+                members.push(CompMember::Comp(ci.clone()));
             });
         }
         _ => {
             // XXX: Some kind of warning would be nice, but this produces far
             //      too many.
-            //log_err_warn(ctx,
-            //    &format!("unhandled composite member `{}` (kind {}) in `{}` ({})",
-            //        cursor.spelling(), cursor.kind(), parent.spelling(), cursor.location()
+            //log_err_warn(ctx, Category::UnsupportedConstruct,
+            //    &format!("unhandled composite member `{}` (kind {}) in `{}`",
+            //        cursor.spelling(), cursor.kind(), parent.spelling()
             //    )[..],
+            //    &cursor.location(),
             //    false
             //);
         }
@@ -440,75 +902,697 @@ fn visit_composite(cursor: &Cursor, parent: &Cursor,
     CXChildVisit_Continue
 }
 
-fn visit_enum(cursor: &Cursor,
+/// Converts a C++ member function, constructor or destructor. Non-virtual
+/// ones are only bound when public, while every virtual one takes a slot in
+/// the vtable.
+fn visit_method(cursor: &Cursor, ctx: &mut ClangParserCtx) -> Option<MethodInfo> {
+    if cursor.access_specifier() != CX_CXXPublic && !cursor.method_is_virtual() {
+        return None;
+    }
+
+    let kind = match cursor.kind() {
+        CXCursor_Constructor => MethodKind::Constructor,
+        CXCursor_Destructor => MethodKind::Destructor,
+        _ if cursor.method_is_static() => MethodKind::Static,
+        _ => MethodKind::Instance
+    };
+    let mangled = match mangled_name(cursor) {
+        Some(mangled) => mangled,
+        None => cursor.spelling()
+    };
+    let sig = match mk_fn_sig(ctx, &cursor.cur_type(), cursor) {
+        Some(sig) => sig,
+        None => return None
+    };
+
+    Some(MethodInfo {
+        name: cursor.spelling(),
+        kind: kind,
+        is_const: cursor.method_is_const(),
+        mangled: mangled,
+        sig: sig,
+        comment: doc_comment(cursor),
+        is_public: cursor.access_specifier() == CX_CXXPublic,
+    })
+}
+
+/// Records the base class of a C++ class. Only a single, non-virtual base
+/// has a layout we can reproduce, so other classes are bound as opaque.
+fn visit_base(cursor: &Cursor, ctx: &mut ClangParserCtx, ci: &mut CompInfo) {
+    let base = conv_ty(ctx, &cursor.cur_type().canonical_type(), cursor);
+    match base {
+        TComp(ref base) if ci.base.is_none() && !cursor.is_virtual_base() => {
+            ci.has_vtable = ci.has_vtable || base.borrow().has_vtable;
+            ci.base = Some(base.clone());
+        }
+        _ => {
+            let msg = format!("Only single, non-virtual inheritance is supported, \
+                               `{}` is bound as opaque.", ci.name);
+            report(ctx, Severity::Warning, Category::UnsupportedConstruct, &msg[..],
+                   &cursor.location());
+            ci.opaque = true;
+        }
+    }
+}
+
+/// Converts an Objective-C interface, category or protocol, with its
+/// methods and the accessors of its properties.
+fn visit_objc(cursor: &Cursor, ctx: &mut ClangParserCtx) -> ObjCInfo {
+    let kind = match cursor.kind() {
+        CXCursor_ObjCInterfaceDecl => ObjCKind::Interface,
+        CXCursor_ObjCCategoryDecl => ObjCKind::Category,
+        _ => ObjCKind::Protocol
+    };
+    let mut oi = ObjCInfo::new(cursor.spelling(), kind);
+    oi.comment = doc_comment(cursor);
+
+    let mut properties = vec!();
+    cursor.visit(|c, _: &Cursor| {
+        match c.kind() {
+            CXCursor_ObjCClassRef if kind == ObjCKind::Category => oi.class = c.spelling(),
+            CXCursor_ObjCInstanceMethodDecl | CXCursor_ObjCClassMethodDecl => {
+                let sig = il::FuncSig {
+                    ret_ty: Box::new(conv_ty(ctx, &c.ret_type(), c)),
+                    args: c.args().iter().map(|arg| {
+                        (arg.spelling(), conv_ty(ctx, &arg.cur_type(), arg))
+                    }).collect(),
+                    is_variadic: false,
+                    abi: abi::C,
+                    noreturn: false,
+                };
+                oi.methods.push(ObjCMethodInfo {
+                    selector: c.spelling(),
+                    is_class: c.kind() == CXCursor_ObjCClassMethodDecl,
+                    sig: sig,
+                    comment: doc_comment(c),
+                });
+            }
+            CXCursor_ObjCPropertyDecl => properties.push(*c),
+            _ => ()
+        }
+        CXChildVisit_Continue
+    });
+
+    // Accessors are only declared implicitly, unless overridden.  Custom
+    // accessor names aren't exposed by libclang, those are left out.
+    for c in properties.iter() {
+        let name = c.spelling();
+        let attrs = c.objc_property_attributes();
+        let ty = conv_ty(ctx, &c.cur_type(), c);
+        let mut setter = format!("set{}{}:", name[..1].to_uppercase(), &name[1..]);
+        if attrs & CXObjCPropertyAttr_readonly != 0 {
+            setter.clear();
+        }
+        if attrs & (CXObjCPropertyAttr_getter | CXObjCPropertyAttr_setter) != 0 {
+            let msg = format!("Custom accessors of property `{}` of `{}` are not supported, \
+                               declare them as methods to bind them.", name, oi.name);
+            report(ctx, Severity::Warning, Category::UnsupportedConstruct, &msg[..], &c.location());
+            if attrs & CXObjCPropertyAttr_setter != 0 {
+                setter.clear();
+            }
+        }
+
+        let accessors = [
+            (if attrs & CXObjCPropertyAttr_getter != 0 { String::new() } else { name.clone() },
+             ty.clone(), vec!()),
+            (setter, TVoid, vec!((name.clone(), ty.clone()))),
+        ];
+        for &(ref selector, ref ret_ty, ref args) in accessors.iter() {
+            if selector.is_empty() || oi.methods.iter().any(|m| !m.is_class && m.selector == *selector) {
+                continue;
+            }
+            oi.methods.push(ObjCMethodInfo {
+                selector: selector.clone(),
+                is_class: false,
+                sig: il::FuncSig {
+                    ret_ty: Box::new(ret_ty.clone()),
+                    args: args.clone(),
+                    is_variadic: false,
+                    abi: abi::C,
+                    noreturn: false,
+                },
+                comment: doc_comment(c),
+            });
+        }
+    }
+    oi
+}
+
+/// The C definition of `wrapper`, an exported function with the signature
+/// of `cursor` forwarding its arguments to `callee`, a `static` function or
+/// a function-like macro, which can't be linked to.
+fn wrapper_def(cursor: &Cursor, wrapper: &str, callee: &str) -> String {
+    // Types like function pointers can't just be followed by a name.
+    fn declare(ty: &cx::Type, declarator: &str) -> String {
+        let spelling = ty.spelling();
+        if spelling.contains('(') || spelling.contains('[') {
+            format!("__typeof__({}) {}", spelling, declarator)
+        } else {
+            format!("{} {}", spelling, declarator)
+        }
+    }
+
+    let args = cursor.args();
+    let params: Vec<String> = args.iter().enumerate().map(|(i, arg)| {
+        declare(&arg.cur_type(), &format!("arg{}", i)[..])
+    }).collect();
+    let params = if params.is_empty() { "void".to_string() } else { params.connect(", ") };
+    let call_args: Vec<String> = (0..args.len()).map(|i| format!("arg{}", i)).collect();
+    let call = format!("{}({})", callee, call_args.connect(", "));
+
+    let ret_ty = cursor.cur_type().ret_type();
+    let body = if ret_ty.kind() == CXType_Void { call } else { format!("return {}", call) };
+    format!("{} {{ {}; }}", declare(&ret_ty, &format!("{}({})", wrapper, params)[..]), body)
+}
+
+/// The name of the function-like macro whose wrapper `cursor` declares.
+fn wrapped_macro(ctx: &ClangParserCtx, cursor: &Cursor) -> Option<String> {
+    let name = cursor.spelling();
+    if !name.ends_with(MACRO_SUFFIX) {
+        return None;
+    }
+    let macro_name = &name[..name.len() - MACRO_SUFFIX.len()];
+    if ctx.wrapped_macros.contains(macro_name) {
+        Some(macro_name.to_string())
+    } else {
+        None
+    }
+}
+
+/// The start and end of the name declared by a function prototype: the
+/// first identifier followed by an opening parenthesis.
+fn prototype_name(prototype: &str) -> Option<(usize, usize)> {
+    for (open, _) in prototype.char_indices().filter(|&(_, c)| c == '(') {
+        let end = prototype[..open].trim_right().len();
+        let start = prototype[..end].rfind(|c: char| !(c == '_' || c.is_alphanumeric()))
+                                    .map_or(0, |i| i + 1);
+        if start < end && !prototype[start..].starts_with(|c: char| c.is_numeric()) {
+            return Some((start, end));
+        }
+    }
+    None
+}
+
+/// Appends the declaration of a wrapper for each function-like macro to
+/// wrap to the main file, so that clang parses their signature in context,
+/// with the types of the headers.
+fn declare_macro_wrappers(ctx: &mut ClangParserCtx, unit: &TranslationUnit) -> Result<(), Error> {
+    let main = unit.cursor().spelling();
+    let mut source = String::new();
+    try!(File::open(&main).and_then(|mut f| f.read_to_string(&mut source)).map_err(|e| {
+        io::Error::new(e.kind(), format!("unable to read {} to declare macro wrappers: {}", main, e))
+    }));
+
+    let mut defined = HashSet::new();
+    unit.cursor().visit(|c, _: &Cursor| {
+        if c.kind() == CXCursor_MacroDefinition {
+            defined.insert(c.spelling());
+        }
+        CXChildVisit_Continue
+    });
+
+    source.push_str("\n");
+    for prototype in ctx.options.wrap_macros.iter() {
+        let (start, end) = match prototype_name(&prototype[..]) {
+            Some(name) => name,
+            None => {
+                return Err(Error::InvalidOption(format!("invalid macro prototype `{}`", prototype)));
+            }
+        };
+        if !defined.contains(&prototype[start..end]) {
+            return Err(Error::InvalidOption(format!("macro prototype `{}` names `{}`, which is not \
+                                                     a defined macro", prototype, &prototype[start..end])));
+        }
+        ctx.wrapped_macros.insert(prototype[start..end].to_string());
+        source.push_str(&format!("{}{}{};\n", &prototype[..end], MACRO_SUFFIX,
+                                 &prototype[end..])[..]);
+    }
+
+    if !unit.reparse(&[UnsavedFile::new(&main[..], &source[..])], 0) {
+        return Err(Error::Parse(clang_diags(ctx, unit)));
+    }
+    Ok(())
+}
+
+/// Logs the diagnostics of clang about `unit`, and returns them.
+fn clang_diags(ctx: &ClangParserCtx, unit: &TranslationUnit) -> Vec<Diagnostic> {
+    let mut diags = vec!();
+    for d in unit.diags().iter() {
+        let mut diag = Diagnostic::new(severity(d.severity()), Category::Clang, d.spelling());
+        diag.location = location(&d.location());
+        ctx.logger.log(&diag);
+        diags.push(diag);
+    }
+    diags
+}
+
+fn visit_enum(cursor: &Cursor, unsigned: bool,
               items: &mut Vec<EnumItem>) -> Enum_CXVisitorResult {
     if cursor.kind() == CXCursor_EnumConstantDecl {
         let name = cursor.spelling();
-        let val = cursor.enum_val();
-        let item = EnumItem::new(name, val);
+        let val = if unsigned {
+            EnumVal::Unsigned(cursor.enum_val_unsigned())
+        } else {
+            EnumVal::Signed(cursor.enum_val())
+        };
+        let mut item = EnumItem::new(name, val);
+        item.comment = doc_comment(cursor);
         items.push(item);
     }
     return CXChildVisit_Continue;
 }
 
+fn num_kind(ty: &cx::Type) -> Option<macros::NumKind> {
+    let kind = match ty.canonical_type().kind() {
+        CXType_Bool => IBool,
+        CXType_SChar | CXType_Char_S => ISChar,
+        CXType_UChar | CXType_Char_U => IUChar,
+        CXType_UShort => IUShort,
+        CXType_UInt => IUInt,
+        CXType_ULong => IULong,
+        CXType_ULongLong => IULongLong,
+        CXType_Short => IShort,
+        CXType_Int => IInt,
+        CXType_Long => ILong,
+        CXType_LongLong => ILongLong,
+        CXType_Int128 => IInt128,
+        CXType_UInt128 => IUInt128,
+        CXType_Float => return Some(macros::NumKind::Float(FFloat)),
+        CXType_Double => return Some(macros::NumKind::Float(FDouble)),
+        _ => return None,
+    };
+    Some(macros::NumKind::Int(kind))
+}
+
+fn visit_macro(cursor: &Cursor, ctx: &mut ClangParserCtx) {
+    let tokens = match cursor.translation_unit().tokens(cursor) {
+        Some(tokens) => tokens,
+        None => return,
+    };
+    if tokens.len() < 2 {
+        return;
+    }
+
+    // Function-like macros have the opening parenthesis directly after
+    // the macro name.
+    let name = tokens[0].spelling.clone();
+    if tokens[1].spelling == "(" && tokens[1].offset == tokens[0].offset + name.len() {
+        return;
+    }
+
+    let val = {
+        let defined = &ctx.macros;
+        let typedefs = &ctx.typedef_kinds;
+        macros::eval(&tokens[1..],
+                     &|n: &str| defined.get(n).cloned(),
+                     &|n: &str| typedefs.get(n).cloned())
+    };
+    let val = match val {
+        Some(val) => val,
+        None => {
+            debug!("unable to evaluate macro `{}` ({})", name, cursor.location());
+            return;
+        }
+    };
+    ctx.macros.insert(name.clone(), val.clone());
+
+    // Predefined macros are never emitted, even with builtins enabled.
+    // Constants are filtered by name like variables.
+    let (file, _, _, _) = cursor.location().location();
+    if file.is_null() || !match_pattern(ctx, cursor) || !ctx.options.vars.allows(&name[..]) ||
+       is_filtering(&ctx.options) && !ctx.options.vars.has_allowlist() {
+        return;
+    }
+
+    let (ty, val) = match val {
+        macros::Value::Int(v, k) => {
+            let cv = if k.is_signed() { ConstVal::Int(v) } else { ConstVal::UInt(v as u64) };
+            (TInt(k, Layout::zero()), cv)
+        }
+        macros::Value::Float(v, k) => (TFloat(k, Layout::zero()), ConstVal::Float(v)),
+        macros::Value::Str(bytes) => {
+            let len = bytes.len();
+            (TArray(Box::new(TInt(IUChar, Layout::new(1, 1))), len, Layout::new(len, 1)),
+             ConstVal::Str(bytes))
+        }
+    };
+    let mut vi = VarInfo::new(name, ty);
+    vi.is_const = true;
+    vi.val = Some(val);
+    ctx.globals.push(GVar(Rc::new(RefCell::new(vi))));
+}
+
+/// Evaluates the initializer of a constant variable the same way as the
+/// body of an object-like macro.
+fn const_initializer(ctx: &ClangParserCtx, cursor: &Cursor) -> Option<macros::Value> {
+    let tokens = match cursor.translation_unit().tokens(cursor) {
+        Some(tokens) => tokens,
+        None => return None,
+    };
+    // The extent of a declarator after the first of a declaration starts
+    // at the declaration, the initializer is the one after its own name.
+    let (_, _, _, name_offset) = cursor.location().location();
+    let name = match tokens.iter().position(|t| t.offset == name_offset) {
+        Some(i) => i + 1,
+        None => return None,
+    };
+    let init = match tokens[name..].iter().position(|t| t.spelling == "=") {
+        Some(i) => name + i + 1,
+        None => return None,
+    };
+
+    let defined = &ctx.macros;
+    let typedefs = &ctx.typedef_kinds;
+    let val = macros::eval(&tokens[init..],
+                           &|n: &str| defined.get(n).cloned(),
+                           &|n: &str| typedefs.get(n).cloned());
+    if val.is_none() {
+        let msg = format!("unable to evaluate the initializer of `{}`", cursor.spelling());
+        let mut diag = Diagnostic::new(Severity::Debug, Category::UnsupportedConstruct, msg);
+        diag.location = location(&cursor.location());
+        ctx.logger.log(&diag);
+    }
+    val
+}
+
+/// Converts the value of an initializer to the declared type of the
+/// variable, as C would on assignment.  Values which can't be represented
+/// as a Rust constant of that type give `None`.
+fn const_val(ty: &il::Type, val: &macros::Value) -> Option<ConstVal> {
+    match (ty, val) {
+        (&TNamed(ref ti), _) => const_val(&ti.borrow().ty, val),
+        (&TInt(kind, ref layout), &macros::Value::Int(v, _)) => {
+            // Truncate to the width of the type, then extend back.
+            let bits = layout.size * 8;
+            let v = if bits > 0 && bits < 64 {
+                let shift = 64 - bits;
+                if kind.is_signed() {
+                    (v << shift) >> shift
+                } else {
+                    ((v as u64) << shift >> shift) as i64
+                }
+            } else {
+                v
+            };
+            Some(if kind.is_signed() { ConstVal::Int(v) } else { ConstVal::UInt(v as u64) })
+        }
+        (&TFloat(..), &macros::Value::Float(v, _)) => Some(ConstVal::Float(v)),
+        (&TFloat(..), &macros::Value::Int(v, kind)) => {
+            Some(ConstVal::Float(if kind.is_signed() { v as f64 } else { v as u64 as f64 }))
+        }
+        (&TArray(ref elem, _, _), &macros::Value::Str(ref bytes)) |
+        (&TPtr(ref elem, _, _), &macros::Value::Str(ref bytes)) => {
+            match **elem {
+                TInt(ISChar, _) | TInt(IUChar, _) => Some(ConstVal::Str(bytes.clone())),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Whether anything is allowlisted, so that only what is, and the types it
+/// needs, is bound.
+fn is_filtering(options: &ClangParserOptions) -> bool {
+    options.functions.has_allowlist() || options.types.has_allowlist() ||
+    options.vars.has_allowlist()
+}
+
 fn visit_top<'r>(cursor: &Cursor,
                  ctx: &mut ClangParserCtx) -> Enum_CXVisitorResult {
+    // Macros and arithmetic typedefs are recorded whichever file they come
+    // from, as macros in bound files may depend on them.
+    match cursor.kind() {
+        CXCursor_MacroDefinition => {
+            visit_macro(cursor, ctx);
+            return CXChildVisit_Continue;
+        }
+        CXCursor_TypedefDecl => {
+            if let Some(kind) = num_kind(&cursor.typedef_type()) {
+                ctx.typedef_kinds.insert(cursor.spelling(), kind);
+            }
+        }
+        _ => (),
+    }
+
     if !match_pattern(ctx, cursor) {
         return CXChildVisit_Continue;
     }
 
+    // Once anything is allowlisted, types that aren't are only bound when
+    // an allowlisted declaration needs them.  Builtins are only visited when
+    // something refers to them.
+    let name = cursor.spelling();
+    let filtering = is_filtering(&ctx.options);
+    let root = match cursor.kind() {
+        CXCursor_StructDecl | CXCursor_UnionDecl | CXCursor_ClassDecl | CXCursor_EnumDecl |
+        CXCursor_TypedefDecl if ctx.options.replaced_types.contains(&name) => {
+            return CXChildVisit_Continue;
+        }
+        _ if ctx.options.builtin_names.contains(&name) => true,
+        CXCursor_StructDecl | CXCursor_UnionDecl | CXCursor_ClassDecl |
+        CXCursor_EnumDecl if name.is_empty() => !filtering,
+        CXCursor_StructDecl | CXCursor_UnionDecl | CXCursor_ClassDecl | CXCursor_EnumDecl |
+        CXCursor_TypedefDecl | CXCursor_ObjCInterfaceDecl | CXCursor_ObjCCategoryDecl |
+        CXCursor_ObjCProtocolDecl => {
+            let allowlisted = ctx.options.types.allowlists(&name[..]);
+            if ctx.options.types.blocks(&name[..]) {
+                return CXChildVisit_Continue;
+            }
+            allowlisted || !filtering
+        }
+        CXCursor_FunctionDecl if wrapped_macro(ctx, cursor).is_some() => true,
+        // Functions and variables aren't needed by anything else, they are
+        // left out unless their own list allows them.
+        CXCursor_FunctionDecl if !ctx.options.functions.allows(&name[..]) ||
+                                 filtering && !ctx.options.functions.has_allowlist() => {
+            return CXChildVisit_Continue;
+        }
+        CXCursor_VarDecl if !ctx.options.vars.allows(&name[..]) ||
+                            filtering && !ctx.options.vars.has_allowlist() => {
+            return CXChildVisit_Continue;
+        }
+        _ => true
+    };
+
+    let first = ctx.globals.len();
+    let res = visit_decl(cursor, ctx);
+    if !root {
+        let new = ctx.globals[first..].to_vec();
+        ctx.deps.extend(new.into_iter());
+    }
+    res
+}
+
+fn visit_decl(cursor: &Cursor, ctx: &mut ClangParserCtx) -> Enum_CXVisitorResult {
     match cursor.kind() {
-        CXCursor_StructDecl | CXCursor_UnionDecl => {
+        CXCursor_Namespace | CXCursor_LinkageSpec => {
+            cursor.visit(|c, _: &Cursor| visit_top(c, ctx));
+            return CXChildVisit_Continue;
+        }
+        CXCursor_StructDecl | CXCursor_UnionDecl | CXCursor_ClassDecl => {
             fwd_decl(ctx, cursor, |ctx_| {
                 let decl = decl_name(ctx_, cursor);
-                let ci = decl.compinfo();
-                cursor.visit(|c, p| {
-                    let mut ci_ = ci.borrow_mut();
-                    visit_composite(c, p, ctx_, &mut ci_.members)
-                });
+                let ci = match decl.compinfo() {
+                    Some(ci) => ci,
+                    None => return kind_mismatch(ctx_, cursor, &decl, "struct or union")
+                };
+                let name = cursor.spelling();
+                let opaque = !name.is_empty() && ctx_.options.opaque_types.allowlists(&name[..]);
+                ci.borrow_mut().comment = doc_comment(cursor);
+                ci.borrow_mut().opaque = opaque;
+                if !opaque {
+                    cursor.visit(|c, p| match c.kind() {
+                        CXCursor_CXXMethod | CXCursor_Constructor | CXCursor_Destructor => {
+                            match visit_method(c, ctx_) {
+                                Some(method) => {
+                                    let mut ci_ = ci.borrow_mut();
+                                    if !c.method_is_virtual() {
+                                        ci_.methods.push(method);
+                                    } else {
+                                        ci_.has_vtable = true;
+                                        if !c.method_is_override() {
+                                            ci_.vmethods.push(method);
+                                        }
+                                    }
+                                }
+                                // The vtable can't be laid out without the
+                                // slot of a virtual method we can't call
+                                None if c.method_is_virtual() && !c.method_is_override() => {
+                                    ci.borrow_mut().opaque = true;
+                                }
+                                None => ()
+                            }
+                            CXChildVisit_Continue
+                        }
+                        CXCursor_CXXBaseSpecifier => {
+                            visit_base(c, ctx_, &mut ci.borrow_mut());
+                            CXChildVisit_Continue
+                        }
+                        _ => {
+                            let mut ci_ = ci.borrow_mut();
+                            visit_composite(c, p, ctx_, &mut ci_.members)
+                        }
+                    });
+                }
                 ctx_.globals.push(GComp(ci));
             });
             return CXChildVisit_Continue;
         }
+        CXCursor_ObjCInterfaceDecl | CXCursor_ObjCCategoryDecl | CXCursor_ObjCProtocolDecl => {
+            let oi = visit_objc(cursor, ctx);
+            // Class extensions, unnamed categories, are private to the
+            // implementation of the class.
+            if oi.kind != ObjCKind::Category || !oi.name.is_empty() {
+                ctx.globals.push(GObjC(Rc::new(RefCell::new(oi))));
+            }
+            return CXChildVisit_Continue;
+        }
         CXCursor_EnumDecl => {
             fwd_decl(ctx, cursor, |ctx_| {
                 let decl = decl_name(ctx_, cursor);
-                let ei = decl.enuminfo();
+                let ei = match decl.enuminfo() {
+                    Some(ei) => ei,
+                    None => return kind_mismatch(ctx_, cursor, &decl, "enum")
+                };
+                ei.borrow_mut().comment = doc_comment(cursor);
+                let unsigned = match num_kind(&cursor.enum_type()) {
+                    Some(macros::NumKind::Int(kind)) => !kind.is_signed(),
+                    _ => false
+                };
                 cursor.visit(|c, _: &Cursor| {
                     let mut ei_ = ei.borrow_mut();
-                    visit_enum(c, &mut ei_.items)
+                    visit_enum(c, unsigned, &mut ei_.items)
                 });
                 ctx_.globals.push(GEnum(ei));
             });
             return CXChildVisit_Continue;
         }
         CXCursor_FunctionDecl => {
+            let name = cursor.spelling();
             let linkage = cursor.linkage();
-            if linkage != CXLinkage_External && linkage != CXLinkage_UniqueExternal {
+            let attrs = decl_attrs(cursor);
+            let macro_name = wrapped_macro(ctx, cursor);
+            let is_static = linkage == CXLinkage_Internal && ctx.options.wrap_static_fns &&
+                            cursor.definition() == *cursor;
+            if (macro_name.is_some() || is_static) && cursor.cur_type().is_variadic() {
+                // Variadic arguments can't be forwarded
+                let msg = format!("Can't wrap variadic function `{}`.", name);
+                report(ctx, Severity::Warning, Category::UnsupportedConstruct, &msg[..],
+                       &cursor.location());
                 return CXChildVisit_Continue;
             }
+            let wrapper = if let Some(ref macro_name) = macro_name {
+                Some((name.clone(), wrapper_def(cursor, &name[..], &macro_name[..])))
+            } else if linkage == CXLinkage_External || linkage == CXLinkage_UniqueExternal {
+                if is_hidden(&attrs) {
+                    let msg = format!("`{}` has hidden visibility and is left out", name);
+                    report(ctx, Severity::Info, Category::UnsupportedConstruct, &msg[..],
+                           &cursor.location());
+                    return CXChildVisit_Continue;
+                }
+                None
+            } else if is_static {
+                // The wrapper is declared `extern "C"` at the top level of
+                // the shim, functions of C++ namespaces are called by their
+                // qualified name.
+                let namespace = cursor_namespace(cursor);
+                let (wrapper, callee) = if namespace.is_empty() {
+                    (format!("{}{}", name, STATIC_FN_SUFFIX), name.clone())
+                } else {
+                    (format!("{}_{}{}", namespace.connect("_"), name, STATIC_FN_SUFFIX),
+                     format!("::{}::{}", namespace.connect("::"), name))
+                };
+                let def = wrapper_def(cursor, &wrapper[..], &callee[..]);
+                Some((wrapper, def))
+            } else {
+                return CXChildVisit_Continue;
+            };
+
+            let sig = match mk_fn_sig(ctx, &cursor.cur_type(), cursor) {
+                Some(sig) => sig,
+                None => return CXChildVisit_Continue
+            };
 
             let func = decl_name(ctx, cursor);
-            let vi = func.varinfo();
+            let vi = match func.varinfo() {
+                Some(vi) => vi,
+                None => {
+                    kind_mismatch(ctx, cursor, &func, "function");
+                    return CXChildVisit_Continue;
+                }
+            };
             let mut vi = vi.borrow_mut();
 
-            vi.ty = TFuncPtr(mk_fn_sig(ctx, &cursor.cur_type(), cursor));
+            vi.ty = TFuncPtr(sig);
+            vi.comment = doc_comment(cursor);
+            vi.deprecated = deprecation(cursor);
+            vi.must_use = attrs.iter().any(|&(k, _)| k == CXCursor_WarnUnusedResultAttr) ||
+                          has_attr(&attrs, &["warn_unused_result", "nodiscard"]);
+            vi.mangled = match wrapper {
+                Some((wrapper, def)) => {
+                    ctx.wrappers.push(def);
+                    Some(wrapper)
+                }
+                None => mangled_name(cursor)
+            };
+            if let Some(macro_name) = macro_name {
+                vi.name = macro_name;
+            }
             ctx.globals.push(func);
 
             return CXChildVisit_Continue;
         }
         CXCursor_VarDecl => {
+            // Variables with internal linkage or hidden visibility can't be
+            // linked to, they are only bound when they are constants with a
+            // known value.
             let linkage = cursor.linkage();
-            if linkage != CXLinkage_External && linkage != CXLinkage_UniqueExternal {
+            let external = (linkage == CXLinkage_External || linkage == CXLinkage_UniqueExternal) &&
+                           !is_hidden(&decl_attrs(cursor));
+            let is_const = cursor.cur_type().is_const();
+            if !external && !is_const {
                 return CXChildVisit_Continue;
             }
 
             let ty = conv_ty(ctx, &cursor.cur_type(), cursor);
+            let val = if is_const { const_initializer(ctx, cursor) } else { None };
+            let val = match val {
+                Some(v) => {
+                    let cv = const_val(&ty, &v);
+                    // Later initializers see the value converted to the type
+                    // of the variable.
+                    let v = match num_kind(&cursor.cur_type()) {
+                        _ if cv.is_none() => None,
+                        Some(kind) => macros::cast(v, kind),
+                        None => Some(v)
+                    };
+                    if let Some(v) = v {
+                        ctx.macros.insert(cursor.spelling(), v);
+                    }
+                    cv
+                }
+                None => None,
+            };
+            if !external && val.is_none() {
+                return CXChildVisit_Continue;
+            }
+
             let var = decl_name(ctx, cursor);
-            let vi = var.varinfo();
+            let vi = match var.varinfo() {
+                Some(vi) => vi,
+                None => {
+                    kind_mismatch(ctx, cursor, &var, "variable");
+                    return CXChildVisit_Continue;
+                }
+            };
             let mut vi = vi.borrow_mut();
             vi.ty = ty.clone();
-            vi.is_const = cursor.cur_type().is_const();
+            vi.is_const = is_const;
+            vi.val = val;
+            vi.comment = doc_comment(cursor);
+            vi.deprecated = deprecation(cursor);
+            vi.mangled = mangled_name(cursor);
             ctx.globals.push(var);
 
             return CXChildVisit_Continue;
@@ -520,10 +1604,22 @@ fn visit_top<'r>(cursor: &Cursor,
             }
 
             let ty = conv_ty(ctx, &under_ty, cursor);
+            if let TComp(ref ci) = ty {
+                if ctx.options.opaque_types.allowlists(&cursor.spelling()[..]) {
+                    ci.borrow_mut().opaque = true;
+                }
+            }
             let typedef = decl_name(ctx, cursor);
-            let ti = typedef.typeinfo();
+            let ti = match typedef.typeinfo() {
+                Some(ti) => ti,
+                None => {
+                    kind_mismatch(ctx, cursor, &typedef, "typedef");
+                    return CXChildVisit_Continue;
+                }
+            };
             let mut ti = ti.borrow_mut();
             ti.ty = ty.clone();
+            ti.comment = doc_comment(cursor);
             ctx.globals.push(typedef);
 
             opaque_ty(ctx, &under_ty);
@@ -537,47 +1633,90 @@ fn visit_top<'r>(cursor: &Cursor,
     }
 }
 
-fn log_err_warn(ctx: &mut ClangParserCtx, msg: &str, is_err: bool) {
-    match is_err {
-        true => {
-            ctx.err_count += 1;
-            ctx.logger.error(msg)
-        },
-        false => ctx.logger.warn(msg)
+/// Reports a construct that can't be bound as an error when `is_err`, or
+/// a warning.
+fn log_err_warn(ctx: &mut ClangParserCtx, category: Category, msg: &str, loc: &SourceLocation,
+                is_err: bool) {
+    let severity = if is_err { Severity::Error } else { Severity::Warning };
+    report(ctx, severity, category, msg, loc);
+}
+
+/// Reports a declaration whose canonical cursor is already bound as another
+/// kind of global, which the IR can't represent.
+fn kind_mismatch(ctx: &mut ClangParserCtx, cursor: &Cursor, decl: &Global, what: &str) {
+    let msg = format!("`{}` can't be bound as a {}, it is already declared as a {}",
+                      cursor.spelling(), what, decl.kind_name());
+    report(ctx, Severity::Error, Category::NameCollision, &msg[..], &cursor.location());
+}
+
+/// Reports a diagnostic about the source at `loc`.  Errors are also
+/// collected, to fail the generation once everything has been reported.
+fn report(ctx: &mut ClangParserCtx, severity: Severity, category: Category, msg: &str,
+          loc: &SourceLocation) {
+    let mut diag = Diagnostic::new(severity, category, msg);
+    diag.location = location(loc);
+    ctx.logger.log(&diag);
+    if severity >= Severity::Error {
+        ctx.errors.push(diag);
     }
 }
 
-pub fn parse(options: ClangParserOptions, logger: &Logger) -> Result<Vec<Global>, ()> {
+fn location(loc: &SourceLocation) -> Option<Location> {
+    let (file, line, col, _) = loc.location();
+    if file.is_null() {
+        return None;
+    }
+    Some(Location {
+        file: file.name(),
+        line: line,
+        column: col,
+    })
+}
+
+fn severity(severity: Enum_CXDiagnosticSeverity) -> Severity {
+    match severity {
+        CXDiagnostic_Ignored | CXDiagnostic_Note => Severity::Note,
+        CXDiagnostic_Warning => Severity::Warning,
+        CXDiagnostic_Error => Severity::Error,
+        _ => Severity::Fatal
+    }
+}
+
+/// Parses the headers into globals, and the C source of the wrappers of
+/// `static` functions and function-like macros, if any are wrapped.
+pub fn parse(options: ClangParserOptions, logger: &Logger) -> Result<(Vec<Global>, String), Error> {
     let mut ctx = ClangParserCtx {
         options: options,
         name: HashMap::new(),
         builtin_defs: vec!(),
         globals: vec!(),
+        deps: vec!(),
+        macros: HashMap::new(),
+        typedef_kinds: HashMap::new(),
+        wrappers: vec!(),
+        wrapped_macros: HashSet::new(),
         logger: logger,
-        err_count: 0
+        errors: vec!()
     };
 
     let ix = cx::Index::create(false, true);
     if ix.is_null() {
-        ctx.logger.error("Clang failed to create index");
-        return Err(())
+        return Err(Error::IndexCreation)
     }
 
-    let unit = TranslationUnit::parse(&ix, "", &ctx.options.clang_args[..], &[], 0);
+    let unit = TranslationUnit::parse(&ix, "", &ctx.options.clang_args[..], &[],
+                                      CXTranslationUnit_DetailedPreprocessingRecord as usize);
     if unit.is_null() {
-        ctx.logger.error("No input files given");
-        return Err(())
+        return Err(Error::NoInput)
     }
 
-    let diags = unit.diags();
-    for d in diags.iter() {
-        let msg = d.format(Diagnostic::default_opts());
-        let is_err = d.severity() >= CXDiagnostic_Error;
-        log_err_warn(&mut ctx, &msg[..], is_err);
+    if !ctx.options.wrap_macros.is_empty() {
+        try!(declare_macro_wrappers(&mut ctx, &unit));
     }
 
-    if ctx.err_count > 0 {
-        return Err(())
+    let diags = clang_diags(&ctx, &unit);
+    if diags.iter().any(|d| d.severity >= Severity::Error) {
+        return Err(Error::Parse(diags))
     }
 
     let cursor = unit.cursor();
@@ -588,6 +1727,24 @@ pub fn parse(options: ClangParserOptions, logger: &Logger) -> Result<Vec<Global>
 
     cursor.visit(|cur, _: &Cursor| visit_top(cur, &mut ctx));
 
+    // The shim is compiled from wherever the build script puts it, so the
+    // header is included by its absolute path. Header names have no escape
+    // sequences, the path is written as is.
+    let shim = if ctx.wrappers.is_empty() {
+        String::new()
+    } else {
+        let main = cursor.spelling();
+        let header = if Path::new(&main).is_absolute() {
+            PathBuf::from(&main)
+        } else {
+            try!(env::current_dir()).join(&main)
+        };
+        format!("/* automatically generated by rust-bindgen */\n\n#include \"{}\"\n\n\
+                 #ifdef __cplusplus\nextern \"C\" {{\n#endif\n\n{}\n\n\
+                 #ifdef __cplusplus\n}}\n#endif\n",
+                header.display(), ctx.wrappers.connect("\n"))
+    };
+
     while !ctx.builtin_defs.is_empty() {
         let c = ctx.builtin_defs.remove(0);
         visit_top(&c.definition(), &mut ctx);
@@ -596,9 +1753,14 @@ pub fn parse(options: ClangParserOptions, logger: &Logger) -> Result<Vec<Global>
     unit.dispose();
     ix.dispose();
 
-    if ctx.err_count > 0 {
-        return Err(())
+    ctx.options.functions.report_unused(ctx.logger);
+    ctx.options.types.report_unused(ctx.logger);
+    ctx.options.vars.report_unused(ctx.logger);
+    ctx.options.opaque_types.report_unused(ctx.logger);
+
+    if !ctx.errors.is_empty() {
+        return Err(Error::Unsupported(ctx.errors))
     }
 
-    Ok(ctx.globals)
+    Ok((reachable_globals(ctx.globals, &ctx.deps[..], ctx.options.opaque_behind_pointers), shim))
 }
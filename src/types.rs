@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::rc::Rc;
 
@@ -8,6 +9,7 @@ pub use self::Global::*;
 pub use self::Type::*;
 pub use self::IKind::*;
 pub use self::FKind::*;
+pub use self::OKind::*;
 
 #[derive(Clone)]
 pub enum Global {
@@ -18,38 +20,66 @@ pub enum Global {
     GEnumDecl(Rc<RefCell<EnumInfo>>),
     GVar(Rc<RefCell<VarInfo>>),
     GFunc(Rc<RefCell<VarInfo>>),
+    GObjC(Rc<RefCell<ObjCInfo>>),
     GOther
 }
 
 impl Global {
-    pub fn compinfo(&self) -> Rc<RefCell<CompInfo>> {
+    /// The composite behind a struct or union global, `None` for any other
+    /// kind of global.
+    pub fn compinfo(&self) -> Option<Rc<RefCell<CompInfo>>> {
         match *self {
-            GComp(ref i) => return i.clone(),
-            GCompDecl(ref i) => return i.clone(),
-            _ => panic!("global_compinfo".to_string())
+            GComp(ref i) | GCompDecl(ref i) => Some(i.clone()),
+            _ => None
         }
     }
 
-    pub fn enuminfo(&self) -> Rc<RefCell<EnumInfo>> {
+    /// The enum behind an enum global, `None` for any other kind of global.
+    pub fn enuminfo(&self) -> Option<Rc<RefCell<EnumInfo>>> {
         match *self {
-            GEnum(ref i) => return i.clone(),
-            GEnumDecl(ref i) => return i.clone(),
-            _ => panic!("global_enuminfo".to_string())
+            GEnum(ref i) | GEnumDecl(ref i) => Some(i.clone()),
+            _ => None
         }
     }
 
-    pub fn typeinfo(&self) -> Rc<RefCell<TypeInfo>> {
+    /// The typedef behind a type global, `None` for any other kind of global.
+    pub fn typeinfo(&self) -> Option<Rc<RefCell<TypeInfo>>> {
         match *self {
-            GType(ref i) => return i.clone(),
-            _ => panic!("global_typeinfo".to_string())
+            GType(ref i) => Some(i.clone()),
+            _ => None
         }
     }
 
-    pub fn varinfo(&self) -> Rc<RefCell<VarInfo>> {
+    /// The variable or function behind a global, `None` for any other kind
+    /// of global.
+    pub fn varinfo(&self) -> Option<Rc<RefCell<VarInfo>>> {
         match *self {
-            GVar(ref i) => i.clone(),
-            GFunc(ref i) => i.clone(),
-            _ => panic!("global_varinfo".to_string())
+            GVar(ref i) | GFunc(ref i) => Some(i.clone()),
+            _ => None
+        }
+    }
+
+    /// What kind of item the global is, for diagnostics.
+    pub fn kind_name(&self) -> &'static str {
+        match *self {
+            GType(_) => "typedef",
+            GComp(_) | GCompDecl(_) => "struct or union",
+            GEnum(_) | GEnumDecl(_) => "enum",
+            GVar(_) => "variable",
+            GFunc(_) => "function",
+            GObjC(_) => "Objective-C declaration",
+            GOther => "different kind of declaration"
+        }
+    }
+
+    /// The C++ namespaces the global is declared in, outermost first.
+    pub fn namespace(&self) -> Vec<String> {
+        match *self {
+            GType(ref ti) => ti.borrow().namespace.clone(),
+            GComp(ref ci) | GCompDecl(ref ci) => ci.borrow().namespace.clone(),
+            GEnum(ref ei) | GEnumDecl(ref ei) => ei.borrow().namespace.clone(),
+            GVar(ref vi) | GFunc(ref vi) => vi.borrow().namespace.clone(),
+            GObjC(_) | GOther => vec!()
         }
     }
 }
@@ -64,17 +94,132 @@ impl fmt::Debug for Global {
             GEnumDecl(ref ei) => ei.borrow().fmt(f),
             GVar(ref vi) => vi.borrow().fmt(f),
             GFunc(ref vi) => vi.borrow().fmt(f),
+            GObjC(ref oi) => oi.borrow().fmt(f),
             GOther => "*".fmt(f),
         }
     }
 }
 
+/// Keeps the globals that aren't listed in `deps`, and those of `deps`
+/// they need, following pointers, arrays, fields, typedefs and function
+/// signatures.  With `opaque_ptrs`, structs and unions from `deps` that are
+/// only needed behind a pointer are kept as opaque declarations.
+pub fn reachable_globals(globs: Vec<Global>, deps: &[Global], opaque_ptrs: bool) -> Vec<Global> {
+    fn addr<T>(info: &Rc<RefCell<T>>) -> usize {
+        &**info as *const RefCell<T> as usize
+    }
+
+    fn global_addr(g: &Global) -> Option<usize> {
+        match *g {
+            GType(ref ti) => Some(addr(ti)),
+            GComp(ref ci) | GCompDecl(ref ci) => Some(addr(ci)),
+            GEnum(ref ei) | GEnumDecl(ref ei) => Some(addr(ei)),
+            GVar(ref vi) | GFunc(ref vi) => Some(addr(vi)),
+            GObjC(ref oi) => Some(addr(oi)),
+            GOther => None
+        }
+    }
+
+    // Maps every type reached to whether it is only needed opaquely, behind
+    // a pointer.  A type is visited again when first reached directly.
+    fn reach(seen: &mut HashMap<usize, bool>, key: usize, opaque: bool) -> bool {
+        match seen.get(&key) {
+            Some(&prev) if prev && !opaque => {}
+            Some(_) => return false,
+            None => {}
+        }
+        seen.insert(key, opaque);
+        true
+    }
+
+    fn visit_sig(seen: &mut HashMap<usize, bool>, sig: &FuncSig, opaque_ptrs: bool) {
+        visit_ty(seen, &sig.ret_ty, false, opaque_ptrs);
+        for &(_, ref ty) in sig.args.iter() {
+            visit_ty(seen, ty, false, opaque_ptrs);
+        }
+    }
+
+    fn visit_comp(seen: &mut HashMap<usize, bool>, ci: &CompInfo, opaque_ptrs: bool) {
+        if ci.opaque {
+            return;
+        }
+        if let Some(ref base) = ci.base {
+            visit_ty(seen, &TComp(base.clone()), false, opaque_ptrs);
+        }
+        for m in ci.methods.iter().chain(ci.vmethods.iter()) {
+            visit_sig(seen, &m.sig, opaque_ptrs);
+        }
+        for m in ci.members.iter() {
+            match *m {
+                CompMember::Field(ref f) => visit_ty(seen, &f.ty, false, opaque_ptrs),
+                CompMember::Comp(ref c) => visit_comp(seen, &c.borrow(), opaque_ptrs),
+                CompMember::CompField(ref c, ref f) => {
+                    visit_comp(seen, &c.borrow(), opaque_ptrs);
+                    visit_ty(seen, &f.ty, false, opaque_ptrs);
+                }
+            }
+        }
+    }
+
+    fn visit_ty(seen: &mut HashMap<usize, bool>, ty: &Type, behind_ptr: bool, opaque_ptrs: bool) {
+        match *ty {
+            TPtr(ref t, _, _) => visit_ty(seen, t, opaque_ptrs, opaque_ptrs),
+            TArray(ref t, _, _) => visit_ty(seen, t, behind_ptr, opaque_ptrs),
+            TFuncProto(ref sig) | TFuncPtr(ref sig) => visit_sig(seen, sig, opaque_ptrs),
+            TNamed(ref ti) => if reach(seen, addr(ti), behind_ptr) {
+                visit_ty(seen, &ti.borrow().ty, behind_ptr, opaque_ptrs);
+            },
+            TComp(ref ci) => {
+                // Anonymous types have no name to declare them by.
+                let opaque = behind_ptr && !ci.borrow().name.is_empty();
+                if reach(seen, addr(ci), opaque) && !opaque {
+                    visit_comp(seen, &ci.borrow(), opaque_ptrs);
+                }
+            }
+            TEnum(ref ei) => { reach(seen, addr(ei), behind_ptr); },
+            TVoid | TInt(..) | TFloat(..) | TObjC(..) => {}
+        }
+    }
+
+    let deps: HashSet<usize> = deps.iter().filter_map(global_addr).collect();
+    let is_root = |g: &Global| global_addr(g).map_or(true, |a| !deps.contains(&a));
+
+    let mut seen = HashMap::new();
+    for g in globs.iter().filter(|g| is_root(*g)) {
+        match *g {
+            GType(ref ti) => visit_ty(&mut seen, &ti.borrow().ty, false, opaque_ptrs),
+            GComp(ref ci) => visit_comp(&mut seen, &ci.borrow(), opaque_ptrs),
+            GVar(ref vi) | GFunc(ref vi) => visit_ty(&mut seen, &vi.borrow().ty, false, opaque_ptrs),
+            GObjC(ref oi) => for m in oi.borrow().methods.iter() {
+                visit_sig(&mut seen, &m.sig, opaque_ptrs);
+            },
+            _ => {}
+        }
+    }
+
+    globs.into_iter().filter_map(|g| {
+        if is_root(&g) {
+            return Some(g);
+        }
+        let opaque = match global_addr(&g).and_then(|a| seen.get(&a)) {
+            Some(&opaque) => opaque,
+            None => return None
+        };
+        match g {
+            GComp(ci) if opaque => Some(GCompDecl(ci)),
+            g => Some(g)
+        }
+    }).collect()
+}
+
 #[derive(Clone, PartialEq)]
 pub struct FuncSig {
     pub ret_ty: Box<Type>,
     pub args: Vec<(String, Type)>,
     pub is_variadic: bool,
     pub abi: abi::Abi,
+    /// Whether the function never returns, as with `noreturn`.
+    pub noreturn: bool,
 }
 
 #[derive(Clone, PartialEq)]
@@ -88,7 +233,8 @@ pub enum Type {
     TFuncPtr(FuncSig),
     TNamed(Rc<RefCell<TypeInfo>>),
     TComp(Rc<RefCell<CompInfo>>),
-    TEnum(Rc<RefCell<EnumInfo>>)
+    TEnum(Rc<RefCell<EnumInfo>>),
+    TObjC(OKind, Layout)
 }
 
 impl Type {
@@ -101,6 +247,7 @@ impl Type {
             &TNamed(ref ti) => ti.borrow().ty.size(),
             &TComp(ref ci) => ci.borrow().layout.size,
             &TEnum(ref ei) => ei.borrow().layout.size,
+            &TObjC(_, l) => l.size,
             &TVoid => 0,
             &TFuncProto(..) => 0,
             &TFuncPtr(..) => 0,
@@ -117,6 +264,7 @@ impl Type {
             &TNamed(ref ti) => ti.borrow().ty.align(),
             &TComp(ref ci) => ci.borrow().layout.align,
             &TEnum(ref ei) => ei.borrow().layout.align,
+            &TObjC(_, l) => l.align,
             &TVoid => 0,
             &TFuncProto(..) => 0,
             &TFuncPtr(..) => 0,
@@ -152,13 +300,63 @@ pub enum IKind {
     ILong,
     IULong,
     ILongLong,
-    IULongLong
+    IULongLong,
+    IInt128,
+    IUInt128
+}
+
+impl IKind {
+    pub fn is_signed(&self) -> bool {
+        match *self {
+            IBool | IUChar | IUShort | IUInt | IULong | IULongLong | IUInt128 => false,
+            ISChar | IShort | IInt | ILong | ILongLong | IInt128 => true,
+        }
+    }
+
+    /// The integer conversion rank of the kind, as used by the usual
+    /// arithmetic conversions.
+    pub fn rank(&self) -> u32 {
+        match *self {
+            IBool => 0,
+            ISChar | IUChar => 1,
+            IShort | IUShort => 2,
+            IInt | IUInt => 3,
+            ILong | IULong => 4,
+            ILongLong | IULongLong => 5,
+            IInt128 | IUInt128 => 6,
+        }
+    }
+
+    pub fn to_unsigned(&self) -> IKind {
+        match *self {
+            ISChar => IUChar,
+            IShort => IUShort,
+            IInt => IUInt,
+            ILong => IULong,
+            ILongLong => IULongLong,
+            IInt128 => IUInt128,
+            k => k,
+        }
+    }
 }
 
 #[derive(Copy, Clone, PartialEq)]
 pub enum FKind {
     FFloat,
-    FDouble
+    FDouble,
+    FLongDouble,
+    FComplexFloat,
+    FComplexDouble,
+    FComplexLongDouble
+}
+
+/// The builtin Objective-C types.
+#[derive(Copy, Clone, PartialEq)]
+pub enum OKind {
+    /// `id`, `instancetype` and pointers to interfaces.
+    OId,
+    OClass,
+    OSel
 }
 
 #[derive(Clone, PartialEq)]
@@ -180,6 +378,18 @@ pub struct CompInfo {
     pub name: String,
     pub members: Vec<CompMember>,
     pub layout: Layout,
+    pub comment: String,
+    /// Bound as a blob of the right size and alignment, without its fields.
+    pub opaque: bool,
+    pub methods: Vec<MethodInfo>,
+    /// The virtual methods adding a slot to the vtable, in vtable order.
+    /// Overrides reuse the slot of the method they override.
+    pub vmethods: Vec<MethodInfo>,
+    /// The non-virtual base class, laid out at the start of the class.
+    pub base: Option<Rc<RefCell<CompInfo>>>,
+    /// Whether the class, or its base, starts with a vtable pointer.
+    pub has_vtable: bool,
+    pub namespace: Vec<String>,
 }
 
 impl CompInfo {
@@ -189,6 +399,13 @@ impl CompInfo {
             name: name,
             members: members,
             layout: layout,
+            comment: String::new(),
+            opaque: false,
+            methods: vec!(),
+            vmethods: vec!(),
+            base: None,
+            has_vtable: false,
+            namespace: vec!(),
         }
     }
 }
@@ -199,19 +416,52 @@ impl fmt::Debug for CompInfo {
     }
 }
 
+#[derive(Copy, Clone, PartialEq)]
+pub enum MethodKind {
+    Instance,
+    Static,
+    Constructor,
+    Destructor,
+}
+
+/// A C++ member function, constructor or destructor.
+#[derive(Clone, PartialEq)]
+pub struct MethodInfo {
+    pub name: String,
+    pub kind: MethodKind,
+    /// Whether `this` is const.
+    pub is_const: bool,
+    /// The symbol to link against.
+    pub mangled: String,
+    /// The signature, without the implicit `this` argument.
+    pub sig: FuncSig,
+    pub comment: String,
+    /// Whether the method is public.  Private and protected virtual methods
+    /// only take their slot in the vtable.
+    pub is_public: bool,
+}
+
 #[derive(Clone, PartialEq)]
 pub struct FieldInfo {
     pub name: String,
     pub ty: Type,
-    pub bitfields: Option<Vec<(String, u32)>>,
+    /// The name, width and offset in bits within the storage unit of every
+    /// bitfield sharing the unit.
+    pub bitfields: Option<Vec<(String, u32, u32)>>,
+    /// Offset in bits from the start of the record, as reported by clang.
+    /// For bitfields this is the offset of their storage unit.
+    pub offset: Option<usize>,
+    pub comment: String,
 }
 
 impl FieldInfo {
-    pub fn new(name: String, ty: Type, bitfields: Option<Vec<(String, u32)>>) -> FieldInfo {
+    pub fn new(name: String, ty: Type, bitfields: Option<Vec<(String, u32, u32)>>) -> FieldInfo {
         FieldInfo {
             name: name,
             ty: ty,
             bitfields: bitfields,
+            offset: None,
+            comment: String::new(),
         }
     }
 }
@@ -222,6 +472,8 @@ pub struct EnumInfo {
     pub items: Vec<EnumItem>,
     pub kind: IKind,
     pub layout: Layout,
+    pub comment: String,
+    pub namespace: Vec<String>,
 }
 
 impl EnumInfo {
@@ -231,6 +483,8 @@ impl EnumInfo {
             items: items,
             kind: kind,
             layout: layout,
+            comment: String::new(),
+            namespace: vec!(),
         }
     }
 }
@@ -244,14 +498,51 @@ impl fmt::Debug for EnumInfo {
 #[derive(Clone, PartialEq)]
 pub struct EnumItem {
     pub name: String,
-    pub val: i64
+    pub val: EnumVal,
+    pub comment: String
 }
 
 impl EnumItem {
-    pub fn new(name: String, val: i64) -> EnumItem {
+    pub fn new(name: String, val: EnumVal) -> EnumItem {
         EnumItem {
             name: name,
-            val: val
+            val: val,
+            comment: String::new()
+        }
+    }
+}
+
+/// The value of an enumerator, read according to the signedness of the
+/// enum's integer type.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EnumVal {
+    Signed(i64),
+    Unsigned(u64),
+}
+
+impl EnumVal {
+    pub fn is_negative(&self) -> bool {
+        match *self {
+            EnumVal::Signed(v) => v < 0,
+            EnumVal::Unsigned(_) => false,
+        }
+    }
+
+    /// The absolute value, which cannot overflow unlike `i64::abs`.
+    pub fn magnitude(&self) -> u64 {
+        match *self {
+            EnumVal::Signed(v) if v < 0 => (v as u64).wrapping_neg(),
+            EnumVal::Signed(v) => v as u64,
+            EnumVal::Unsigned(v) => v,
+        }
+    }
+}
+
+impl fmt::Display for EnumVal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EnumVal::Signed(v) => v.fmt(f),
+            EnumVal::Unsigned(v) => v.fmt(f),
         }
     }
 }
@@ -259,14 +550,18 @@ impl EnumItem {
 #[derive(Clone, PartialEq)]
 pub struct TypeInfo {
     pub name: String,
-    pub ty: Type
+    pub ty: Type,
+    pub comment: String,
+    pub namespace: Vec<String>,
 }
 
 impl TypeInfo {
     pub fn new(name: String, ty: Type) -> TypeInfo {
         TypeInfo {
             name: name,
-            ty: ty
+            ty: ty,
+            comment: String::new(),
+            namespace: vec!(),
         }
     }
 }
@@ -277,11 +572,31 @@ impl fmt::Debug for TypeInfo {
     }
 }
 
+/// The value of a constant known at generation time, e.g. a `#define`.
+#[derive(Clone, PartialEq)]
+pub enum ConstVal {
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Str(Vec<u8>),
+}
+
 #[derive(Clone)]
 pub struct VarInfo {
     pub name: String,
     pub ty: Type,
-    pub is_const: bool
+    pub is_const: bool,
+    pub val: Option<ConstVal>,
+    pub comment: String,
+    pub namespace: Vec<String>,
+    /// The symbol to link against, when it isn't the name.
+    pub mangled: Option<String>,
+    /// `Some` when the declaration is deprecated, with the message of the
+    /// attribute, which is empty if it has none.
+    pub deprecated: Option<String>,
+    /// Whether the result of the function must be used, as with
+    /// `warn_unused_result`.
+    pub must_use: bool,
 }
 
 impl VarInfo {
@@ -289,7 +604,13 @@ impl VarInfo {
         VarInfo {
             name: name,
             ty: ty,
-            is_const: false
+            is_const: false,
+            val: None,
+            comment: String::new(),
+            namespace: vec!(),
+            mangled: None,
+            deprecated: None,
+            must_use: false,
         }
     }
 }
@@ -299,3 +620,53 @@ impl fmt::Debug for VarInfo {
         self.name.fmt(f)
     }
 }
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ObjCKind {
+    Interface,
+    Category,
+    Protocol,
+}
+
+/// An Objective-C interface, category or protocol.
+#[derive(Clone)]
+pub struct ObjCInfo {
+    pub kind: ObjCKind,
+    pub name: String,
+    /// The interface a category extends.
+    pub class: String,
+    /// The declared methods, followed by the accessors of the properties
+    /// that aren't declared as methods.
+    pub methods: Vec<ObjCMethodInfo>,
+    pub comment: String,
+}
+
+impl ObjCInfo {
+    pub fn new(name: String, kind: ObjCKind) -> ObjCInfo {
+        ObjCInfo {
+            kind: kind,
+            name: name,
+            class: String::new(),
+            methods: vec!(),
+            comment: String::new(),
+        }
+    }
+}
+
+impl fmt::Debug for ObjCInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.name.fmt(f)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ObjCMethodInfo {
+    /// The selector, e.g. `initWithFrame:style:`.
+    pub selector: String,
+    /// Whether it is a class method, sent to the class rather than to an
+    /// instance.
+    pub is_class: bool,
+    /// The signature, without the implicit `self` and `_cmd` arguments.
+    pub sig: FuncSig,
+    pub comment: String,
+}
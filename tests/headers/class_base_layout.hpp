@@ -0,0 +1,18 @@
+class Base {
+public:
+    virtual ~Base();
+    int id;
+};
+
+// `width` is placed in the tail padding of `Base`.
+class Derived : public Base {
+public:
+    int width;
+};
+
+struct Empty {};
+
+// `Empty` takes no space in `WithEmptyBase`.
+struct WithEmptyBase : Empty {
+    int value;
+};
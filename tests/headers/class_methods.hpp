@@ -0,0 +1,16 @@
+namespace ns {
+
+class Counter {
+public:
+    Counter(int start);
+    ~Counter();
+    void increment(int by);
+    int get() const;
+    static int instances();
+private:
+    int count;
+};
+
+int total(const Counter &c);
+
+}
@@ -0,0 +1,12 @@
+typedef int mylib_size;
+typedef int other_size;
+
+#define mylib_max 16
+#define OTHER_MAX 32
+
+extern mylib_size mylib_count;
+extern other_size other_count;
+
+mylib_size mylib_init(void);
+int mylib_internal_init(void);
+int other_init(void);
@@ -0,0 +1,12 @@
+#define BASE_PORT 8000
+
+/** The port used when none is given. */
+static const int DEFAULT_PORT = BASE_PORT + 80;
+static const unsigned char SMALL = 0x1ff;
+static const int SMALL_NEXT = SMALL + 1;
+const double RATIO = DEFAULT_PORT / 2;
+const char NAME[] = "bg";
+static const int FIRST = 1, SECOND = 2;
+extern const int LIMIT;
+static const int TABLE[] = { 1, 2 };
+int counter = 3;
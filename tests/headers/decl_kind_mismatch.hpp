@@ -0,0 +1,2 @@
+using count_t = int;
+typedef int count_t;
@@ -0,0 +1,8 @@
+struct stat { int st_mode; };
+int stat(const char *path, struct stat *buf);
+
+typedef struct node node;
+struct node { node *next; };
+
+enum color { RED };
+int color;
@@ -0,0 +1,2 @@
+int valid(int x);
+int broken(int x;
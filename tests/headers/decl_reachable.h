@@ -0,0 +1,25 @@
+struct inner {
+    int x;
+};
+
+struct outer {
+    struct inner inner;
+    struct hidden *h;
+};
+
+struct hidden {
+    int secret;
+};
+
+typedef struct outer outer_t;
+
+struct unused {
+    int y;
+};
+
+outer_t *lib_make(void);
+int lib_size(struct outer o);
+void other(struct unused *u);
+
+extern int lib_count;
+#define LIB_VERSION 3
@@ -0,0 +1,11 @@
+struct timespec {
+    long tv_sec;
+    long tv_nsec;
+};
+
+typedef int handle_t;
+
+struct event {
+    struct timespec when;
+    handle_t *owner;
+};
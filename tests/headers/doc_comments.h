@@ -0,0 +1,15 @@
+/** A point in space. */
+struct point {
+    /** The x coordinate. */
+    int x;
+    int y; /**< The y coordinate. */
+};
+
+/**
+ * Adds two numbers.
+ *
+ * @param a the first number
+ * @param b the second number
+ * @return the sum
+ */
+int add(int a, int b);
@@ -0,0 +1,10 @@
+enum big_unsigned {
+    BIG_MAX = 0xffffffffffffffffULL,
+    BIG_HIGH = 0x8000000000000000ULL,
+    BIG_ZERO = 0
+};
+
+enum big_signed {
+    SIGNED_MIN = -0x7fffffffffffffffLL - 1,
+    SIGNED_MAX = 0x7fffffffffffffffLL
+};
@@ -0,0 +1,4 @@
+enum flags {
+    FLAGS_READ = 1 << 0,
+    FLAGS_WRITE = 1 << 1,
+};
@@ -0,0 +1,9 @@
+enum plain { PLAIN_A, PLAIN_B };
+enum rusty { RUSTY_A, RUSTY_B, RUSTY_C = 0 };
+enum moduled { MODULED_A = -1, MODULED_B };
+enum wrapped { WRAPPED_A = 1, WRAPPED_B = 2 };
+
+struct foo {
+    enum moduled m;
+    enum wrapped w;
+};
@@ -0,0 +1,8 @@
+int plain(int x);
+int __attribute__((intel_ocl_bicc)) ocl(int x);
+
+struct callbacks {
+    int (*cb)(int) __attribute__((intel_ocl_bicc));
+};
+
+int __attribute__((sysv_abi)) sysv(int x);
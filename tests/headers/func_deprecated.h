@@ -0,0 +1,3 @@
+void old_api(void) __attribute__((deprecated));
+void older_api(int x) __attribute__((deprecated("use new_api instead")));
+extern int old_count __attribute__((deprecated));
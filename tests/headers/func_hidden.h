@@ -0,0 +1,3 @@
+void public_fn(void);
+void internal_fn(void) __attribute__((visibility("hidden")));
+extern int internal_count __attribute__((visibility("hidden")));
@@ -0,0 +1,2 @@
+int try_lock(void) __attribute__((warn_unused_result));
+void unlock(void);
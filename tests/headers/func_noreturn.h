@@ -0,0 +1,3 @@
+void fatal(const char *msg) __attribute__((noreturn));
+_Noreturn void quit(int code);
+void (*on_exit_cb)(void) __attribute__((noreturn));
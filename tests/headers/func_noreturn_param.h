@@ -0,0 +1 @@
+void set_handler(void (*h)(void) __attribute__((noreturn)));
@@ -0,0 +1,4 @@
+static inline int add(int a, int b) { return a + b; }
+static inline int apply(int (*f)(int x), int x) { return f(x); }
+static void reset(void) {}
+int twice(int x);
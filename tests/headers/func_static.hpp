@@ -0,0 +1,3 @@
+namespace util {
+static inline int clamp(int x) { return x < 0 ? 0 : x; }
+}
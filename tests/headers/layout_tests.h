@@ -0,0 +1,9 @@
+struct foo {
+    char a;
+    int b;
+};
+
+union bar {
+    int c;
+    char d;
+};
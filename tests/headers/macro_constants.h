@@ -0,0 +1,17 @@
+#define FLAG_A 1
+#define FLAG_B (1 << 4)
+#define FLAG_AB (FLAG_A | FLAG_B)
+#define NEG -5
+#define BIG 0xFFFFFFFFFFFFFFFFULL
+#define LONG_VAL 10L
+#define CAST ((unsigned char)0x1ff)
+#define PI 3.5
+#define HALF_F 0.5f
+#define CHAR_C 'a'
+#define STR "hi\n"
+#define CONCAT "a" "b"
+#define FUNC_LIKE(x) ((x) + 1)
+#define EMPTY
+#define EXPR (2 * (3 + 4) - 1)
+typedef __int128 wide_t;
+#define WIDE ((wide_t)1 << 100)
@@ -0,0 +1,6 @@
+struct foo {
+    unsigned flags;
+};
+
+#define FOO_GET_FLAGS(x) ((x)->flags & 0xff)
+#define FOO_RESET(x) ((x)->flags = 0)
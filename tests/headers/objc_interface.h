@@ -0,0 +1,16 @@
+@protocol Shape
+- (int)area;
+@end
+
+/// A counter.
+@interface Counter
+@property int count;
+@property (readonly) int limit;
+- (instancetype)initWithStart:(int)start limit:(int)limit;
+- (void)incrementBy:(int)by;
++ (id)counterWithStart:(int)start;
+@end
+
+@interface Counter (Reset)
+- (void)reset;
+@end
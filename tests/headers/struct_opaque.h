@@ -0,0 +1,10 @@
+struct hidden {
+    int a;
+    char b;
+    double c;
+};
+
+struct visible {
+    struct hidden h;
+    int x;
+};
@@ -0,0 +1,21 @@
+struct aligned_field {
+    char a;
+    char b __attribute__((aligned(4)));
+    int c;
+    struct {
+        char d;
+        int e;
+    };
+};
+
+#pragma pack(push, 2)
+struct pack2 {
+    char a;
+    int b;
+    char c;
+};
+#pragma pack(pop)
+
+struct aligned16 {
+    int a;
+} __attribute__((aligned(16)));
@@ -0,0 +1,9 @@
+enum mode { MODE_A, MODE_B };
+
+struct bitfield_offsets {
+    unsigned char a;
+    unsigned int b : 3;
+    unsigned int c : 5;
+    unsigned short d;
+    enum mode m : 2;
+};
@@ -0,0 +1,8 @@
+struct foo {
+    __int128 a;
+    unsigned __int128 b;
+    float _Complex c;
+    double _Complex d;
+    long double e;
+    long double _Complex f;
+};
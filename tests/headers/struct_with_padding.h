@@ -0,0 +1,8 @@
+struct foo {
+    char a;
+    int b __attribute__((aligned(8)));
+    struct {
+        char c;
+        int d;
+    };
+};
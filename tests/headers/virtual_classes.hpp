@@ -0,0 +1,16 @@
+class Shape {
+public:
+    virtual ~Shape();
+    virtual int area() const = 0;
+    virtual void scale(int factor);
+    long id;
+private:
+    virtual void resized();
+};
+
+class Square : public Shape {
+public:
+    virtual int area() const;
+    virtual int side();
+    int width;
+};
@@ -1,5 +1,5 @@
 use bindgen;
-use bindgen::{Logger, BindgenOptions};
+use bindgen::{Logger, BindgenOptions, Error};
 
 use std::default::Default;
 
@@ -23,8 +23,12 @@ impl Logger for TestLogger {
     }
 }
 
-pub fn generate_bindings(filename: &str) -> Result<Vec<P<ast::Item>>, ()> {
-    let mut options:BindgenOptions = Default::default();
+pub fn generate_bindings(filename: &str) -> Result<Vec<P<ast::Item>>, Error> {
+    generate_bindings_with(Default::default(), filename)
+}
+
+pub fn generate_bindings_with(mut options: BindgenOptions,
+                              filename: &str) -> Result<Vec<P<ast::Item>>, Error> {
     options.clang_args.push(filename.to_string());
 
     let logger = TestLogger;
@@ -32,9 +36,14 @@ pub fn generate_bindings(filename: &str) -> Result<Vec<P<ast::Item>>, ()> {
 }
 
 pub fn assert_bind_eq(filename: &str, reference_items_str: &str)
+{
+    assert_bind_eq_with(Default::default(), filename, reference_items_str)
+}
+
+pub fn assert_bind_eq_with(options: BindgenOptions, filename: &str, reference_items_str: &str)
 {
     let ext_cx = mk_dummy_ext_ctxt();
-    let generated_items = generate_bindings(&format!("tests/{}", filename)[..]).unwrap();
+    let generated_items = generate_bindings_with(options, &format!("tests/{}", filename)[..]).unwrap();
 
     let mut parser = parse::new_parser_from_source_str(ext_cx.parse_sess(), ext_cx.cfg(), "".to_string(), reference_items_str.to_string());
     let mut reference_items = Vec::new();
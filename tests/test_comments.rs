@@ -0,0 +1,35 @@
+use support::assert_bind_eq;
+
+#[test]
+fn doc_comments() {
+    assert_bind_eq("headers/doc_comments.h", "
+        #[doc = \"A point in space.\"]
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_point {
+            #[doc = \"The x coordinate.\"]
+            pub x: ::libc::c_int,
+            #[doc = \"The y coordinate.\"]
+            pub y: ::libc::c_int,
+        }
+        impl ::std::clone::Clone for Struct_point {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_point {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        extern \"C\" {
+            #[doc = \"Adds two numbers.\"]
+            #[doc = \"\"]
+            #[doc = \"# Arguments\"]
+            #[doc = \"\"]
+            #[doc = \"* `a` - the first number\"]
+            #[doc = \"* `b` - the second number\"]
+            #[doc = \"\"]
+            #[doc = \"# Returns\"]
+            #[doc = \"\"]
+            #[doc = \"the sum\"]
+            pub fn add(a: ::libc::c_int, b: ::libc::c_int) -> ::libc::c_int;
+        }
+    ");
+}
@@ -0,0 +1,217 @@
+use support::assert_bind_eq;
+
+#[test]
+fn class_methods() {
+    assert_bind_eq("headers/class_methods.hpp", "
+        pub mod ns {
+            #[repr(C)]
+            #[derive(Copy)]
+            pub struct Struct_Counter {
+                pub count: ::libc::c_int,
+            }
+            impl ::std::clone::Clone for Struct_Counter {
+                fn clone(&self) -> Self { *self }
+            }
+            impl ::std::default::Default for Struct_Counter {
+                fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+            }
+            extern \"C\" {
+                #[link_name = \"_ZN2ns7CounterC1Ei\"]
+                pub fn Counter_new(this: *mut Struct_Counter, start: ::libc::c_int) -> ();
+                #[link_name = \"_ZN2ns7CounterD1Ev\"]
+                pub fn Counter_destruct(this: *mut Struct_Counter) -> ();
+                #[link_name = \"_ZN2ns7Counter9incrementEi\"]
+                pub fn Counter_increment(this: *mut Struct_Counter, by: ::libc::c_int) -> ();
+                #[link_name = \"_ZNK2ns7Counter3getEv\"]
+                pub fn Counter_get(this: *const Struct_Counter) -> ::libc::c_int;
+                #[link_name = \"_ZN2ns7Counter9instancesEv\"]
+                pub fn Counter_instances() -> ::libc::c_int;
+            }
+            impl Struct_Counter {
+                #[inline]
+                pub unsafe fn new(this: *mut Self, start: ::libc::c_int) {
+                    Counter_new(this, start)
+                }
+                #[inline]
+                pub unsafe fn destruct(&mut self) { Counter_destruct(self) }
+                #[inline]
+                pub unsafe fn increment(&mut self, by: ::libc::c_int) {
+                    Counter_increment(self, by)
+                }
+                #[inline]
+                pub unsafe fn get(&self) -> ::libc::c_int { Counter_get(self) }
+                #[inline]
+                pub unsafe fn instances() -> ::libc::c_int { Counter_instances() }
+            }
+            extern \"C\" {
+                #[link_name = \"_ZN2ns5totalERKNS_7CounterE\"]
+                pub fn total(c: *const Struct_Counter) -> ::libc::c_int;
+            }
+        }
+    ");
+}
+
+#[test]
+fn virtual_classes() {
+    assert_bind_eq("headers/virtual_classes.hpp", "
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_Shape {
+            pub _vtable: *const Struct_Shape_Vtable,
+            pub id: ::libc::c_long,
+        }
+        impl ::std::clone::Clone for Struct_Shape {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_Shape {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        /// The virtual methods of `Shape`, in vtable order.
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_Shape_Vtable {
+            pub destruct: ::std::option::Option<
+                extern \"C\" fn(this: *mut Struct_Shape) -> ()>,
+            pub destruct_deleting: ::std::option::Option<
+                extern \"C\" fn(this: *mut Struct_Shape) -> ()>,
+            pub area: ::std::option::Option<
+                extern \"C\" fn(this: *const Struct_Shape) -> ::libc::c_int>,
+            pub scale: ::std::option::Option<
+                extern \"C\" fn(this: *mut Struct_Shape,
+                              factor: ::libc::c_int) -> ()>,
+            pub resized: ::std::option::Option<
+                extern \"C\" fn(this: *mut Struct_Shape) -> ()>,
+        }
+        impl ::std::clone::Clone for Struct_Shape_Vtable {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_Shape_Vtable {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        impl Struct_Shape {
+            #[inline]
+            pub unsafe fn destruct(&mut self) {
+                let vtable = *(self as *const Self as *const *const Struct_Shape_Vtable);
+                ((*vtable).destruct.unwrap())(self)
+            }
+            #[inline]
+            pub unsafe fn area(&self) -> ::libc::c_int {
+                let vtable = *(self as *const Self as *const *const Struct_Shape_Vtable);
+                ((*vtable).area.unwrap())(self)
+            }
+            #[inline]
+            pub unsafe fn scale(&mut self, factor: ::libc::c_int) {
+                let vtable = *(self as *const Self as *const *const Struct_Shape_Vtable);
+                ((*vtable).scale.unwrap())(self, factor)
+            }
+        }
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_Square {
+            pub _base: Struct_Shape,
+            pub width: ::libc::c_int,
+        }
+        impl ::std::clone::Clone for Struct_Square {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_Square {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        /// The virtual methods of `Square`, in vtable order.
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_Square_Vtable {
+            pub _base: Struct_Shape_Vtable,
+            pub side: ::std::option::Option<
+                extern \"C\" fn(this: *mut Struct_Square) -> ::libc::c_int>,
+        }
+        impl ::std::clone::Clone for Struct_Square_Vtable {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_Square_Vtable {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        impl Struct_Square {
+            #[inline]
+            pub unsafe fn side(&mut self) -> ::libc::c_int {
+                let vtable = *(self as *const Self as *const *const Struct_Square_Vtable);
+                ((*vtable).side.unwrap())(self)
+            }
+        }
+    ");
+}
+
+#[test]
+fn base_layout() {
+    assert_bind_eq("headers/class_base_layout.hpp", "
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_Base {
+            pub _vtable: *const Struct_Base_Vtable,
+            pub id: ::libc::c_int,
+        }
+        impl ::std::clone::Clone for Struct_Base {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_Base {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        /// The virtual methods of `Base`, in vtable order.
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_Base_Vtable {
+            pub destruct: ::std::option::Option<
+                extern \"C\" fn(this: *mut Struct_Base) -> ()>,
+            pub destruct_deleting: ::std::option::Option<
+                extern \"C\" fn(this: *mut Struct_Base) -> ()>,
+        }
+        impl ::std::clone::Clone for Struct_Base_Vtable {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_Base_Vtable {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        impl Struct_Base {
+            #[inline]
+            pub unsafe fn destruct(&mut self) {
+                let vtable = *(self as *const Self as *const *const Struct_Base_Vtable);
+                ((*vtable).destruct.unwrap())(self)
+            }
+        }
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_Derived {
+            pub _vtable: *const Struct_Base_Vtable,
+            pub id: ::libc::c_int,
+            pub width: ::libc::c_int,
+        }
+        impl ::std::clone::Clone for Struct_Derived {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_Derived {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_Empty {
+            pub _bindgen_padding_1_: [u8; 1usize],
+        }
+        impl ::std::clone::Clone for Struct_Empty {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_Empty {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_WithEmptyBase {
+            pub value: ::libc::c_int,
+        }
+        impl ::std::clone::Clone for Struct_WithEmptyBase {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_WithEmptyBase {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+    ");
+}
@@ -1,4 +1,8 @@
-use support::assert_bind_eq;
+use std::default::Default;
+
+use bindgen::{Bindings, BindgenOptions, Category, Error, Severity};
+
+use support::{assert_bind_eq, assert_bind_eq_with, generate_bindings, generate_bindings_with};
 
 #[test]
 fn ptr_to_array() {
@@ -8,3 +12,185 @@ fn ptr_to_array() {
         }
     ");
 }
+
+#[test]
+fn allowlist_and_blocklist() {
+    let mut options: BindgenOptions = Default::default();
+    options.allowlist_functions.push("mylib_.*".to_string());
+    options.blocklist_functions.push("mylib_internal_.*".to_string());
+    options.allowlist_types.push("mylib_.*".to_string());
+    options.allowlist_vars.push("mylib_.*".to_string());
+    assert_bind_eq_with(options, "headers/decl_allowlist.h", "
+        pub type mylib_size = ::libc::c_int;
+        pub const mylib_max: ::libc::c_int = 16;
+        extern \"C\" {
+            pub static mut mylib_count: mylib_size;
+        }
+        extern \"C\" {
+            pub fn mylib_init() -> mylib_size;
+        }
+    ");
+}
+
+#[test]
+fn allowlist_reachable_types() {
+    let mut options: BindgenOptions = Default::default();
+    options.allowlist_functions.push("lib_.*".to_string());
+    options.opaque_behind_pointers = true;
+    assert_bind_eq_with(options, "headers/decl_reachable.h", "
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_inner {
+            pub x: ::libc::c_int,
+        }
+        impl ::std::clone::Clone for Struct_inner {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_inner {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_outer {
+            pub inner: Struct_inner,
+            pub h: *mut Struct_hidden,
+        }
+        impl ::std::clone::Clone for Struct_outer {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_outer {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        pub enum Struct_hidden { }
+        pub type outer_t = Struct_outer;
+        extern \"C\" {
+            pub fn lib_make() -> *mut outer_t;
+            pub fn lib_size(o: Struct_outer) -> ::libc::c_int;
+        }
+    ");
+}
+
+#[test]
+fn replaced_types() {
+    let mut options: BindgenOptions = Default::default();
+    options.type_replacements.push(("timespec".to_string(), "::libc::timespec".to_string()));
+    options.type_replacements.push(("handle_t".to_string(), "my::Handle".to_string()));
+    assert_bind_eq_with(options, "headers/decl_replaced_type.h", "
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_event {
+            pub when: ::libc::timespec,
+            pub owner: *mut my::Handle,
+        }
+        impl ::std::clone::Clone for Struct_event {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_event {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+    ");
+}
+
+#[test]
+fn invalid_replaced_type() {
+    let mut options: BindgenOptions = Default::default();
+    options.type_replacements.push(("handle_t".to_string(), "my::Handle<".to_string()));
+    match generate_bindings_with(options, "tests/headers/decl_replaced_type.h") {
+        Err(Error::InvalidOption(msg)) => assert!(msg.contains("handle_t")),
+        _ => panic!("expected the replacement to be rejected")
+    }
+}
+
+#[test]
+fn const_vars() {
+    assert_bind_eq("headers/decl_const_vars.h", "
+        pub const BASE_PORT: ::libc::c_int = 8000;
+        #[doc = \"The port used when none is given.\"]
+        pub const DEFAULT_PORT: ::libc::c_int = 8080;
+        pub const SMALL: ::libc::c_uchar = 255;
+        pub const SMALL_NEXT: ::libc::c_int = 256;
+        pub const RATIO: ::libc::c_double = 4040.0;
+        pub const NAME: &'static [u8; 3usize] = b\"bg\\0\";
+        pub const FIRST: ::libc::c_int = 1;
+        pub const SECOND: ::libc::c_int = 2;
+        extern \"C\" {
+            pub static LIMIT: ::libc::c_int;
+            pub static mut counter: ::libc::c_int;
+        }
+    ");
+}
+
+#[test]
+fn parse_error() {
+    match generate_bindings("tests/headers/decl_parse_error.h") {
+        Err(Error::Parse(diags)) => {
+            let error = diags.iter().find(|d| d.severity == Severity::Error).unwrap();
+            let location = error.location.as_ref().unwrap();
+            assert!(location.file.ends_with("decl_parse_error.h"));
+            assert_eq!(location.line, 2);
+        }
+        _ => panic!("expected a parse error")
+    }
+}
+
+#[test]
+fn no_input() {
+    let options: BindgenOptions = Default::default();
+    match Bindings::generate(&options, None, None) {
+        Err(Error::NoInput) => (),
+        _ => panic!("expected a missing input error")
+    }
+}
+
+#[test]
+fn name_collision() {
+    assert_bind_eq("headers/decl_name_collision.h", "
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_stat {
+            pub st_mode: ::libc::c_int,
+        }
+        impl ::std::clone::Clone for Struct_stat {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_stat {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        pub type node = Struct_node;
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_node {
+            pub next: *mut node,
+        }
+        impl ::std::clone::Clone for Struct_node {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_node {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        pub type Enum_color = ::libc::c_uint;
+        pub const RED: ::libc::c_uint = 0;
+        extern \"C\" {
+            pub static mut color: ::libc::c_int;
+        }
+        extern \"C\" {
+            pub fn stat(path: *const ::libc::c_char, buf: *mut Struct_stat) -> ::libc::c_int;
+        }
+    ");
+}
+
+#[test]
+fn kind_mismatch() {
+    // The typedef redeclares the alias, its canonical declaration.
+    match generate_bindings("tests/headers/decl_kind_mismatch.hpp") {
+        Err(Error::Unsupported(diags)) => {
+            let diag = diags.iter().find(|d| d.category == Category::NameCollision).unwrap();
+            assert_eq!(diag.severity, Severity::Error);
+            assert!(diag.message.contains("count_t"));
+            let location = diag.location.as_ref().unwrap();
+            assert!(location.file.ends_with("decl_kind_mismatch.hpp"));
+            assert_eq!(location.line, 2);
+        }
+        _ => panic!("expected a name collision error")
+    }
+}
@@ -0,0 +1,178 @@
+use std::default::Default;
+
+use bindgen::{BindgenOptions, EnumStyle, Error};
+
+use support::{assert_bind_eq, assert_bind_eq_with, generate_bindings_with};
+
+#[test]
+fn styles() {
+    let mut options: BindgenOptions = Default::default();
+    options.enum_styles.push(("rusty".to_string(), EnumStyle::Rust));
+    options.enum_styles.push(("moduled".to_string(), EnumStyle::Module));
+    options.enum_styles.push(("wrapped".to_string(), EnumStyle::NewType));
+    assert_bind_eq_with(options, "headers/enum_styles.h", "
+        pub type Enum_plain = ::libc::c_uint;
+        pub const PLAIN_A: ::libc::c_uint = 0;
+        pub const PLAIN_B: ::libc::c_uint = 1;
+        #[repr(u32)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum Enum_rusty {
+            RUSTY_A = 0,
+            RUSTY_B = 1,
+        }
+        impl Enum_rusty {
+            pub const RUSTY_C: Enum_rusty = Enum_rusty::RUSTY_A;
+        }
+        pub mod Enum_moduled {
+            pub type Type = ::libc::c_int;
+            pub const MODULED_A: Type = -1;
+            pub const MODULED_B: Type = 0;
+        }
+        #[repr(transparent)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct Enum_wrapped(pub ::libc::c_uint);
+        impl Enum_wrapped {
+            pub const WRAPPED_A: Enum_wrapped = Enum_wrapped(1);
+            pub const WRAPPED_B: Enum_wrapped = Enum_wrapped(2);
+        }
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_foo {
+            pub m: Enum_moduled::Type,
+            pub w: Enum_wrapped,
+        }
+        impl ::std::clone::Clone for Struct_foo {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_foo {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+    ");
+}
+
+#[test]
+fn style_patterns() {
+    let mut options: BindgenOptions = Default::default();
+    options.enum_styles.push(("r.*y".to_string(), EnumStyle::Rust));
+    options.enum_styles.push(("wrap".to_string(), EnumStyle::NewType));
+    assert_bind_eq_with(options, "headers/enum_styles.h", "
+        pub type Enum_plain = ::libc::c_uint;
+        pub const PLAIN_A: ::libc::c_uint = 0;
+        pub const PLAIN_B: ::libc::c_uint = 1;
+        #[repr(u32)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum Enum_rusty {
+            RUSTY_A = 0,
+            RUSTY_B = 1,
+        }
+        impl Enum_rusty {
+            pub const RUSTY_C: Enum_rusty = Enum_rusty::RUSTY_A;
+        }
+        pub type Enum_moduled = ::libc::c_int;
+        pub const MODULED_A: ::libc::c_int = -1;
+        pub const MODULED_B: ::libc::c_int = 0;
+        pub type Enum_wrapped = ::libc::c_uint;
+        pub const WRAPPED_A: ::libc::c_uint = 1;
+        pub const WRAPPED_B: ::libc::c_uint = 2;
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_foo {
+            pub m: Enum_moduled,
+            pub w: Enum_wrapped,
+        }
+        impl ::std::clone::Clone for Struct_foo {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_foo {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+    ");
+
+    let mut options: BindgenOptions = Default::default();
+    options.enum_styles.push(("(".to_string(), EnumStyle::Rust));
+    match generate_bindings_with(options, "tests/headers/enum_styles.h") {
+        Err(Error::InvalidOption(_)) => (),
+        _ => panic!("expected an invalid enum style pattern to be rejected")
+    }
+}
+
+#[test]
+fn bitflags() {
+    let mut options: BindgenOptions = Default::default();
+    options.enum_styles.push(("flags".to_string(), EnumStyle::Bitflags));
+    assert_bind_eq_with(options, "headers/enum_bitflags.h", "
+        #[repr(transparent)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct Enum_flags(pub ::libc::c_uint);
+        impl Enum_flags {
+            pub const FLAGS_READ: Enum_flags = Enum_flags(1);
+            pub const FLAGS_WRITE: Enum_flags = Enum_flags(2);
+        }
+        impl ::std::ops::BitOr for Enum_flags {
+            type Output = Enum_flags;
+            #[inline]
+            fn bitor(self, other: Enum_flags) -> Enum_flags { Enum_flags(self.0 | other.0) }
+        }
+        impl ::std::ops::BitOrAssign for Enum_flags {
+            #[inline]
+            fn bitor_assign(&mut self, other: Enum_flags) { self.0 |= other.0; }
+        }
+        impl ::std::ops::BitAnd for Enum_flags {
+            type Output = Enum_flags;
+            #[inline]
+            fn bitand(self, other: Enum_flags) -> Enum_flags { Enum_flags(self.0 & other.0) }
+        }
+        impl ::std::ops::BitAndAssign for Enum_flags {
+            #[inline]
+            fn bitand_assign(&mut self, other: Enum_flags) { self.0 &= other.0; }
+        }
+        impl ::std::ops::BitXor for Enum_flags {
+            type Output = Enum_flags;
+            #[inline]
+            fn bitxor(self, other: Enum_flags) -> Enum_flags { Enum_flags(self.0 ^ other.0) }
+        }
+        impl ::std::ops::BitXorAssign for Enum_flags {
+            #[inline]
+            fn bitxor_assign(&mut self, other: Enum_flags) { self.0 ^= other.0; }
+        }
+        impl ::std::ops::Not for Enum_flags {
+            type Output = Enum_flags;
+            #[inline]
+            fn not(self) -> Enum_flags { Enum_flags(!self.0) }
+        }
+    ");
+}
+
+#[test]
+fn values_64bit() {
+    assert_bind_eq("headers/enum_64bit.h", "
+        pub type Enum_big_unsigned = ::libc::c_ulong;
+        pub const BIG_MAX: ::libc::c_ulong = 18446744073709551615;
+        pub const BIG_HIGH: ::libc::c_ulong = 9223372036854775808;
+        pub const BIG_ZERO: ::libc::c_ulong = 0;
+        pub type Enum_big_signed = ::libc::c_long;
+        pub const SIGNED_MIN: ::libc::c_long = -9223372036854775808;
+        pub const SIGNED_MAX: ::libc::c_long = 9223372036854775807;
+    ");
+}
+
+#[test]
+fn values_64bit_rust_style() {
+    let mut options: BindgenOptions = Default::default();
+    options.enum_style = EnumStyle::Rust;
+    assert_bind_eq_with(options, "headers/enum_64bit.h", "
+        #[repr(u64)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum Enum_big_unsigned {
+            BIG_MAX = 18446744073709551615,
+            BIG_HIGH = 9223372036854775808,
+            BIG_ZERO = 0,
+        }
+        #[repr(i64)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum Enum_big_signed {
+            SIGNED_MIN = -9223372036854775808,
+            SIGNED_MAX = 9223372036854775807,
+        }
+    ");
+}
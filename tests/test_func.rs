@@ -1,4 +1,9 @@
-use support::assert_bind_eq;
+use std::default::Default;
+use std::env;
+
+use bindgen::{Bindings, BindgenOptions, Category, CollectingLogger, Error, Logger, Severity};
+
+use support::{assert_bind_eq, assert_bind_eq_with, generate_bindings_with};
 
 #[test]
 fn func_ptr() {
@@ -56,3 +61,207 @@ fn with_array_arg() {
         }
     ");
 }
+
+fn wrap_static_fns_options() -> BindgenOptions {
+    let mut options: BindgenOptions = Default::default();
+    options.wrap_static_fns = true;
+    options
+}
+
+#[test]
+fn static_fns() {
+    assert_bind_eq_with(wrap_static_fns_options(), "headers/func_static.h", "
+        extern \"C\" {
+            #[link_name = \"add__bindgen_static\"]
+            pub fn add(a: ::libc::c_int, b: ::libc::c_int) -> ::libc::c_int;
+            #[link_name = \"apply__bindgen_static\"]
+            pub fn apply(f: ::std::option::Option<extern \"C\" fn(x: ::libc::c_int)
+                                                     -> ::libc::c_int>,
+                         x: ::libc::c_int) -> ::libc::c_int;
+            #[link_name = \"reset__bindgen_static\"]
+            pub fn reset() -> ();
+            pub fn twice(x: ::libc::c_int) -> ::libc::c_int;
+        }
+    ");
+}
+
+#[test]
+fn static_fns_shim() {
+    let mut options = wrap_static_fns_options();
+    options.clang_args.push("tests/headers/func_static.h".to_string());
+    let bindings = Bindings::generate(&options, None, None).unwrap();
+    let header = env::current_dir().unwrap().join("tests/headers/func_static.h");
+    assert_eq!(bindings.static_fns_shim(), &format!("\
+/* automatically generated by rust-bindgen */
+
+#include \"{}\"
+
+#ifdef __cplusplus
+extern \"C\" {{
+#endif
+
+int add__bindgen_static(int arg0, int arg1) {{ return add(arg0, arg1); }}
+int apply__bindgen_static(__typeof__(int (*)(int)) arg0, int arg1) {{ return apply(arg0, arg1); }}
+void reset__bindgen_static(void) {{ reset(); }}
+
+#ifdef __cplusplus
+}}
+#endif
+", header.display())[..]);
+}
+
+#[test]
+fn static_fns_cxx() {
+    assert_bind_eq_with(wrap_static_fns_options(), "headers/func_static.hpp", "
+        pub mod util {
+            extern \"C\" {
+                #[link_name = \"util_clamp__bindgen_static\"]
+                pub fn clamp(x: ::libc::c_int) -> ::libc::c_int;
+            }
+        }
+    ");
+}
+
+#[test]
+fn static_fns_shim_cxx() {
+    let mut options = wrap_static_fns_options();
+    options.clang_args.push("tests/headers/func_static.hpp".to_string());
+    let bindings = Bindings::generate(&options, None, None).unwrap();
+    let header = env::current_dir().unwrap().join("tests/headers/func_static.hpp");
+    assert_eq!(bindings.static_fns_shim(), &format!("\
+/* automatically generated by rust-bindgen */
+
+#include \"{}\"
+
+#ifdef __cplusplus
+extern \"C\" {{
+#endif
+
+int util_clamp__bindgen_static(int arg0) {{ return ::util::clamp(arg0); }}
+
+#ifdef __cplusplus
+}}
+#endif
+", header.display())[..]);
+}
+
+#[test]
+fn noreturn() {
+    assert_bind_eq("headers/func_noreturn.h", "
+        extern \"C\" {
+            pub static mut on_exit_cb: ::std::option::Option<extern \"C\" fn() -> !>;
+        }
+        extern \"C\" {
+            pub fn fatal(msg: *const ::libc::c_char) -> !;
+            pub fn quit(code: ::libc::c_int) -> !;
+        }
+    ");
+}
+
+#[test]
+fn noreturn_param() {
+    assert_bind_eq("headers/func_noreturn_param.h", "
+        extern \"C\" {
+            pub fn set_handler(h: ::std::option::Option<extern \"C\" fn() -> !>) -> ();
+        }
+    ");
+}
+
+#[test]
+fn deprecated() {
+    assert_bind_eq("headers/func_deprecated.h", "
+        extern \"C\" {
+            #[deprecated]
+            pub static mut old_count: ::libc::c_int;
+        }
+        extern \"C\" {
+            #[deprecated]
+            pub fn old_api() -> ();
+            #[deprecated(note = \"use new_api instead\")]
+            pub fn older_api(x: ::libc::c_int) -> ();
+        }
+    ");
+}
+
+#[test]
+fn must_use() {
+    assert_bind_eq("headers/func_must_use.h", "
+        extern \"C\" {
+            #[must_use]
+            pub fn try_lock() -> ::libc::c_int;
+            pub fn unlock() -> ();
+        }
+    ");
+}
+
+#[test]
+fn hidden_visibility() {
+    assert_bind_eq("headers/func_hidden.h", "
+        extern \"C\" {
+            pub fn public_fn() -> ();
+        }
+    ");
+}
+
+#[test]
+fn unsupported_calling_conv() {
+    assert_bind_eq("headers/func_calling_conv.h", "
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_callbacks {
+            pub cb: *mut ::libc::c_void,
+        }
+        impl ::std::clone::Clone for Struct_callbacks {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_callbacks {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        extern \"C\" {
+            pub fn plain(x: ::libc::c_int) -> ::libc::c_int;
+            pub fn sysv(x: ::libc::c_int) -> ::libc::c_int;
+        }
+    ");
+}
+
+#[test]
+fn unsupported_calling_conv_fails() {
+    let mut options: BindgenOptions = Default::default();
+    options.fail_on_unknown_type = true;
+    match generate_bindings_with(options, "tests/headers/func_calling_conv.h") {
+        Err(Error::Unsupported(diags)) => {
+            let lines: Vec<usize> = diags.iter().map(|d| d.location.as_ref().unwrap().line).collect();
+            assert_eq!(lines, vec!(2, 5));
+            assert!(diags.iter().all(|d| d.severity == Severity::Error));
+        }
+        _ => panic!("expected an unsupported construct error")
+    }
+}
+
+#[test]
+fn unsupported_calling_conv_diagnostics() {
+    let mut options: BindgenOptions = Default::default();
+    options.clang_args.push("tests/headers/func_calling_conv.h".to_string());
+    let logger = CollectingLogger::new();
+    assert!(Bindings::generate(&options, Some(&logger as &Logger), None).is_ok());
+    let diags: Vec<_> = logger.into_diagnostics().into_iter().filter(|d| {
+        d.category == Category::UnsupportedConstruct
+    }).collect();
+    assert_eq!(diags.len(), 2);
+    assert!(diags.iter().all(|d| d.severity == Severity::Warning));
+    assert_eq!(diags[0].location.as_ref().unwrap().line, 2);
+}
+
+#[test]
+fn sysv_calling_conv_on_windows() {
+    let mut options: BindgenOptions = Default::default();
+    options.clang_args.push("-target".to_string());
+    options.clang_args.push("x86_64-pc-windows-msvc".to_string());
+    options.clang_args.push("tests/headers/func_calling_conv.h".to_string());
+    let logger = CollectingLogger::new();
+    let bindings = Bindings::generate(&options, Some(&logger as &Logger), None).unwrap();
+    assert!(!bindings.to_string().contains("fn sysv"));
+    assert!(logger.into_diagnostics().iter().any(|d| {
+        d.category == Category::UnsupportedConstruct && d.message.contains("`sysv`")
+    }));
+}
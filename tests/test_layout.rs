@@ -0,0 +1,99 @@
+use std::default::Default;
+
+use bindgen::BindgenOptions;
+
+use support::assert_bind_eq_with;
+
+#[test]
+fn layout_tests() {
+    let mut options: BindgenOptions = Default::default();
+    options.layout_tests = true;
+    assert_bind_eq_with(options, "headers/layout_tests.h", "
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_foo {
+            pub a: ::libc::c_char,
+            pub b: ::libc::c_int,
+        }
+        impl ::std::clone::Clone for Struct_foo {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_foo {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        #[test]
+        fn bindgen_test_layout_Struct_foo() {
+            assert_eq!(::std::mem::size_of::<Struct_foo>(), 8usize);
+            assert_eq!(::std::mem::align_of::<Struct_foo>(), 4usize);
+            unsafe {
+                let val: Struct_foo = ::std::mem::zeroed();
+                let base = ::std::ptr::addr_of!(val) as usize;
+                assert_eq!(::std::ptr::addr_of!(val.a) as usize - base, 0usize);
+                assert_eq!(::std::ptr::addr_of!(val.b) as usize - base, 4usize);
+            }
+        }
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Union_bar {
+            pub _bindgen_data_: [u32; 1usize],
+        }
+        impl Union_bar {
+            pub unsafe fn c(&mut self) -> *mut ::libc::c_int {
+                let raw: *mut u8 = ::std::mem::transmute(&self._bindgen_data_);
+                ::std::mem::transmute(raw.offset(0))
+            }
+            pub unsafe fn d(&mut self) -> *mut ::libc::c_char {
+                let raw: *mut u8 = ::std::mem::transmute(&self._bindgen_data_);
+                ::std::mem::transmute(raw.offset(0))
+            }
+        }
+        impl ::std::clone::Clone for Union_bar {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Union_bar {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        #[test]
+        fn bindgen_test_layout_Union_bar() {
+            assert_eq!(::std::mem::size_of::<Union_bar>(), 4usize);
+            assert_eq!(::std::mem::align_of::<Union_bar>(), 4usize);
+            unsafe {
+                let mut val: Union_bar = ::std::mem::zeroed();
+                let base = ::std::ptr::addr_of!(val) as usize;
+                assert_eq!(val.c() as usize - base, 0usize);
+                assert_eq!(val.d() as usize - base, 0usize);
+            }
+        }
+    ");
+}
+
+#[test]
+fn packed_layout_tests() {
+    let mut options: BindgenOptions = Default::default();
+    options.layout_tests = true;
+    assert_bind_eq_with(options, "headers/struct_packed.h", "
+        #[repr(C, packed)]
+        #[derive(Copy)]
+        pub struct Struct_foo {
+            pub a: ::libc::c_char,
+            pub b: ::libc::c_int,
+        }
+        impl ::std::clone::Clone for Struct_foo {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_foo {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        #[test]
+        fn bindgen_test_layout_Struct_foo() {
+            assert_eq!(::std::mem::size_of::<Struct_foo>(), 5usize);
+            assert_eq!(::std::mem::align_of::<Struct_foo>(), 1usize);
+            unsafe {
+                let val: Struct_foo = ::std::mem::zeroed();
+                let base = ::std::ptr::addr_of!(val) as usize;
+                assert_eq!(::std::ptr::addr_of!(val.a) as usize - base, 0usize);
+                assert_eq!(::std::ptr::addr_of!(val.b) as usize - base, 1usize);
+            }
+        }
+    ");
+}
@@ -0,0 +1,90 @@
+use std::default::Default;
+use std::env;
+
+use bindgen::{Bindings, BindgenOptions, Error};
+
+use support::{assert_bind_eq, assert_bind_eq_with, generate_bindings_with};
+
+#[test]
+fn constants() {
+    assert_bind_eq("headers/macro_constants.h", "
+        pub type wide_t = i128;
+        pub const FLAG_A: ::libc::c_int = 1;
+        pub const FLAG_B: ::libc::c_int = 16;
+        pub const FLAG_AB: ::libc::c_int = 17;
+        pub const NEG: ::libc::c_int = -5;
+        pub const BIG: ::libc::c_ulonglong = 18446744073709551615;
+        pub const LONG_VAL: ::libc::c_long = 10;
+        pub const CAST: ::libc::c_uchar = 255;
+        pub const PI: ::libc::c_double = 3.5;
+        pub const HALF_F: ::libc::c_float = 0.5;
+        pub const CHAR_C: ::libc::c_char = 97;
+        pub const STR: &'static [u8; 4usize] = b\"hi\\n\\0\";
+        pub const CONCAT: &'static [u8; 3usize] = b\"ab\\0\";
+        pub const EXPR: ::libc::c_int = 13;
+    ");
+}
+
+fn wrap_macros_options() -> BindgenOptions {
+    let mut options: BindgenOptions = Default::default();
+    options.wrap_macros.push("unsigned FOO_GET_FLAGS(const struct foo *x)".to_string());
+    options.wrap_macros.push("void FOO_RESET(struct foo *x)".to_string());
+    options
+}
+
+#[test]
+fn wrapped() {
+    assert_bind_eq_with(wrap_macros_options(), "headers/macro_wrapped.h", "
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_foo {
+            pub flags: ::libc::c_uint,
+        }
+        impl ::std::clone::Clone for Struct_foo {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_foo {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        extern \"C\" {
+            #[link_name = \"FOO_GET_FLAGS__bindgen_macro\"]
+            pub fn FOO_GET_FLAGS(x: *const Struct_foo) -> ::libc::c_uint;
+            #[link_name = \"FOO_RESET__bindgen_macro\"]
+            pub fn FOO_RESET(x: *mut Struct_foo) -> ();
+        }
+    ");
+}
+
+#[test]
+fn wrapped_shim() {
+    let mut options = wrap_macros_options();
+    options.clang_args.push("tests/headers/macro_wrapped.h".to_string());
+    let bindings = Bindings::generate(&options, None, None).unwrap();
+    let header = env::current_dir().unwrap().join("tests/headers/macro_wrapped.h");
+    assert_eq!(bindings.static_fns_shim(), &format!("\
+/* automatically generated by rust-bindgen */
+
+#include \"{}\"
+
+#ifdef __cplusplus
+extern \"C\" {{
+#endif
+
+unsigned int FOO_GET_FLAGS__bindgen_macro(const struct foo * arg0) {{ return FOO_GET_FLAGS(arg0); }}
+void FOO_RESET__bindgen_macro(struct foo * arg0) {{ FOO_RESET(arg0); }}
+
+#ifdef __cplusplus
+}}
+#endif
+", header.display())[..]);
+}
+
+#[test]
+fn wrapped_undefined() {
+    let mut options = wrap_macros_options();
+    options.wrap_macros.push("int FOO_MISSING(struct foo *x)".to_string());
+    match generate_bindings_with(options, "tests/headers/macro_wrapped.h") {
+        Err(Error::InvalidOption(msg)) => assert!(msg.contains("FOO_MISSING")),
+        _ => panic!("expected an invalid option error")
+    }
+}
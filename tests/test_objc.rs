@@ -0,0 +1,61 @@
+use std::default::Default;
+
+use bindgen::BindgenOptions;
+
+use support::assert_bind_eq_with;
+
+fn objc_options() -> BindgenOptions {
+    let mut options: BindgenOptions = Default::default();
+    options.clang_args.push("-x".to_string());
+    options.clang_args.push("objective-c".to_string());
+    options
+}
+
+#[test]
+fn interface_category_and_protocol() {
+    assert_bind_eq_with(objc_options(), "headers/objc_interface.h", "
+        /// An Objective-C object, to which messages are sent with the `objc`
+        /// crate's `msg_send!`.
+        pub type id = *mut ::objc::runtime::Object;
+        pub trait Protocol_Shape {
+            unsafe fn area(self) -> ::libc::c_int;
+        }
+        impl Protocol_Shape for id {
+            unsafe fn area(self) -> ::libc::c_int { msg_send![self, area] }
+        }
+        /// A counter.
+        pub trait Counter {
+            unsafe fn initWithStart_limit_(self, start: ::libc::c_int,
+                                           limit: ::libc::c_int) -> id;
+            unsafe fn incrementBy_(self, by: ::libc::c_int);
+            unsafe fn counterWithStart_(start: ::libc::c_int) -> id;
+            unsafe fn count(self) -> ::libc::c_int;
+            unsafe fn setCount_(self, count: ::libc::c_int);
+            unsafe fn limit(self) -> ::libc::c_int;
+        }
+        impl Counter for id {
+            unsafe fn initWithStart_limit_(self, start: ::libc::c_int,
+                                           limit: ::libc::c_int) -> id {
+                msg_send![self, initWithStart:start limit:limit]
+            }
+            unsafe fn incrementBy_(self, by: ::libc::c_int) {
+                msg_send![self, incrementBy:by]
+            }
+            unsafe fn counterWithStart_(start: ::libc::c_int) -> id {
+                msg_send![::objc::runtime::Class::get(\"Counter\").unwrap(),
+                          counterWithStart:start]
+            }
+            unsafe fn count(self) -> ::libc::c_int { msg_send![self, count] }
+            unsafe fn setCount_(self, count: ::libc::c_int) {
+                msg_send![self, setCount:count]
+            }
+            unsafe fn limit(self) -> ::libc::c_int { msg_send![self, limit] }
+        }
+        pub trait Counter_Reset {
+            unsafe fn reset(self);
+        }
+        impl Counter_Reset for id {
+            unsafe fn reset(self) { msg_send![self, reset] }
+        }
+    ");
+}
@@ -1,4 +1,8 @@
-use support::assert_bind_eq;
+use std::default::Default;
+
+use bindgen::BindgenOptions;
+
+use support::{assert_bind_eq, assert_bind_eq_with};
 
 #[test]
 fn with_anon_struct() {
@@ -293,6 +297,108 @@ fn with_bitfields() {
             pub _bindgen_bitfield_3_: ::libc::c_uint,
         }
 
+        impl Struct_bitfield {
+            #[inline]
+            pub fn a(&self) -> ::libc::c_ushort {
+                let unit = self._bindgen_bitfield_1_ as u64;
+                let shift = if cfg!(target_endian = "big") { 15 } else { 0 };
+                ((unit >> shift) & 0x1u64) as ::libc::c_ushort
+            }
+            #[inline]
+            pub fn set_a(&mut self, val: ::libc::c_ushort) {
+                let unit = self._bindgen_bitfield_1_ as u64;
+                let shift = if cfg!(target_endian = "big") { 15 } else { 0 };
+                let mask = 0x1u64 << shift;
+                self._bindgen_bitfield_1_ = ((unit & !mask) | ((val as u64) << shift & mask)) as ::libc::c_ushort;
+            }
+            #[inline]
+            pub fn b(&self) -> ::libc::c_ushort {
+                let unit = self._bindgen_bitfield_1_ as u64;
+                let shift = if cfg!(target_endian = "big") { 14 } else { 1 };
+                ((unit >> shift) & 0x1u64) as ::libc::c_ushort
+            }
+            #[inline]
+            pub fn set_b(&mut self, val: ::libc::c_ushort) {
+                let unit = self._bindgen_bitfield_1_ as u64;
+                let shift = if cfg!(target_endian = "big") { 14 } else { 1 };
+                let mask = 0x1u64 << shift;
+                self._bindgen_bitfield_1_ = ((unit & !mask) | ((val as u64) << shift & mask)) as ::libc::c_ushort;
+            }
+            #[inline]
+            pub fn c(&self) -> ::libc::c_ushort {
+                let unit = self._bindgen_bitfield_1_ as u64;
+                let shift = if cfg!(target_endian = "big") { 13 } else { 2 };
+                ((unit >> shift) & 0x1u64) as ::libc::c_ushort
+            }
+            #[inline]
+            pub fn set_c(&mut self, val: ::libc::c_ushort) {
+                let unit = self._bindgen_bitfield_1_ as u64;
+                let shift = if cfg!(target_endian = "big") { 13 } else { 2 };
+                let mask = 0x1u64 << shift;
+                self._bindgen_bitfield_1_ = ((unit & !mask) | ((val as u64) << shift & mask)) as ::libc::c_ushort;
+            }
+            #[inline]
+            pub fn d(&self) -> ::libc::c_ushort {
+                let unit = self._bindgen_bitfield_1_ as u64;
+                let shift = if cfg!(target_endian = "big") { 8 } else { 6 };
+                ((unit >> shift) & 0x3u64) as ::libc::c_ushort
+            }
+            #[inline]
+            pub fn set_d(&mut self, val: ::libc::c_ushort) {
+                let unit = self._bindgen_bitfield_1_ as u64;
+                let shift = if cfg!(target_endian = "big") { 8 } else { 6 };
+                let mask = 0x3u64 << shift;
+                self._bindgen_bitfield_1_ = ((unit & !mask) | ((val as u64) << shift & mask)) as ::libc::c_ushort;
+            }
+            #[inline]
+            pub fn new_bitfield_1(a: ::libc::c_ushort, b: ::libc::c_ushort, c: ::libc::c_ushort, d: ::libc::c_ushort) -> ::libc::c_ushort {
+                let mut unit = 0u64;
+                unit |= (((a as u64) & 0x1u64) << (if cfg!(target_endian = "big") { 15 } else { 0 }));
+                unit |= (((b as u64) & 0x1u64) << (if cfg!(target_endian = "big") { 14 } else { 1 }));
+                unit |= (((c as u64) & 0x1u64) << (if cfg!(target_endian = "big") { 13 } else { 2 }));
+                unit |= (((d as u64) & 0x3u64) << (if cfg!(target_endian = "big") { 8 } else { 6 }));
+                unit as ::libc::c_ushort
+            }
+            #[inline]
+            pub fn f(&self) -> ::libc::c_uint {
+                let unit = self._bindgen_bitfield_2_ as u64;
+                let shift = if cfg!(target_endian = "big") { 30 } else { 0 };
+                ((unit >> shift) & 0x3u64) as ::libc::c_uint
+            }
+            #[inline]
+            pub fn set_f(&mut self, val: ::libc::c_uint) {
+                let unit = self._bindgen_bitfield_2_ as u64;
+                let shift = if cfg!(target_endian = "big") { 30 } else { 0 };
+                let mask = 0x3u64 << shift;
+                self._bindgen_bitfield_2_ = ((unit & !mask) | ((val as u64) << shift & mask)) as ::libc::c_uint;
+            }
+            #[inline]
+            pub fn new_bitfield_2(f: ::libc::c_uint) -> ::libc::c_uint {
+                let mut unit = 0u64;
+                unit |= (((f as u64) & 0x3u64) << (if cfg!(target_endian = "big") { 30 } else { 0 }));
+                unit as ::libc::c_uint
+            }
+            #[inline]
+            pub fn g(&self) -> ::libc::c_uint {
+                let unit = self._bindgen_bitfield_3_ as u64;
+                let shift = if cfg!(target_endian = "big") { 0 } else { 0 };
+                ((unit >> shift) & 0xffffffffu64) as ::libc::c_uint
+            }
+            #[inline]
+            pub fn set_g(&mut self, val: ::libc::c_uint) {
+                let unit = self._bindgen_bitfield_3_ as u64;
+                let shift = if cfg!(target_endian = "big") { 0 } else { 0 };
+                let mask = 0xffffffffu64 << shift;
+                self._bindgen_bitfield_3_ = ((unit & !mask) | ((val as u64) << shift & mask)) as ::libc::c_uint;
+            }
+            #[inline]
+            pub fn new_bitfield_3(g: ::libc::c_uint) -> ::libc::c_uint {
+                let mut unit = 0u64;
+                unit |= (((g as u64) & 0xffffffffu64) << (if cfg!(target_endian = "big") { 0 } else { 0 }));
+                unit as ::libc::c_uint
+            }
+        }
+
         impl ::std::clone::Clone for Struct_bitfield {
             fn clone(&self) -> Self { *self }
         }
@@ -303,6 +409,230 @@ fn with_bitfields() {
     ");
 }
 
+#[test]
+fn with_bitfield_offsets() {
+    assert_bind_eq("headers/struct_with_bitfield_offsets.h", "
+        pub type Enum_mode = ::libc::c_uint;
+        pub const MODE_A: ::libc::c_uint = 0;
+        pub const MODE_B: ::libc::c_uint = 1;
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_bitfield_offsets {
+            pub a: ::libc::c_uchar,
+            pub _bindgen_bitfield_1_: ::libc::c_uchar,
+            pub d: ::libc::c_ushort,
+            pub _bindgen_bitfield_2_: ::libc::c_uint,
+        }
+
+        impl Struct_bitfield_offsets {
+            #[inline]
+            pub fn b(&self) -> ::libc::c_uint {
+                let unit = self._bindgen_bitfield_1_ as u64;
+                let shift = if cfg!(target_endian = "big") { 5 } else { 0 };
+                ((unit >> shift) & 0x7u64) as ::libc::c_uint
+            }
+            #[inline]
+            pub fn set_b(&mut self, val: ::libc::c_uint) {
+                let unit = self._bindgen_bitfield_1_ as u64;
+                let shift = if cfg!(target_endian = "big") { 5 } else { 0 };
+                let mask = 0x7u64 << shift;
+                self._bindgen_bitfield_1_ = ((unit & !mask) | ((val as u64) << shift & mask)) as ::libc::c_uchar;
+            }
+            #[inline]
+            pub fn c(&self) -> ::libc::c_uint {
+                let unit = self._bindgen_bitfield_1_ as u64;
+                let shift = if cfg!(target_endian = "big") { 0 } else { 3 };
+                ((unit >> shift) & 0x1fu64) as ::libc::c_uint
+            }
+            #[inline]
+            pub fn set_c(&mut self, val: ::libc::c_uint) {
+                let unit = self._bindgen_bitfield_1_ as u64;
+                let shift = if cfg!(target_endian = "big") { 0 } else { 3 };
+                let mask = 0x1fu64 << shift;
+                self._bindgen_bitfield_1_ = ((unit & !mask) | ((val as u64) << shift & mask)) as ::libc::c_uchar;
+            }
+            #[inline]
+            pub fn new_bitfield_1(b: ::libc::c_uint, c: ::libc::c_uint) -> ::libc::c_uchar {
+                let mut unit = 0u64;
+                unit |= (((b as u64) & 0x7u64) << (if cfg!(target_endian = "big") { 5 } else { 0 }));
+                unit |= (((c as u64) & 0x1fu64) << (if cfg!(target_endian = "big") { 0 } else { 3 }));
+                unit as ::libc::c_uchar
+            }
+            #[inline]
+            pub fn m(&self) -> ::libc::c_uint {
+                let unit = self._bindgen_bitfield_2_ as u64;
+                let shift = if cfg!(target_endian = "big") { 30 } else { 0 };
+                ((unit >> shift) & 0x3u64) as ::libc::c_uint
+            }
+            #[inline]
+            pub fn set_m(&mut self, val: ::libc::c_uint) {
+                let unit = self._bindgen_bitfield_2_ as u64;
+                let shift = if cfg!(target_endian = "big") { 30 } else { 0 };
+                let mask = 0x3u64 << shift;
+                self._bindgen_bitfield_2_ = ((unit & !mask) | ((val as u64) << shift & mask)) as ::libc::c_uint;
+            }
+            #[inline]
+            pub fn new_bitfield_2(m: ::libc::c_uint) -> ::libc::c_uint {
+                let mut unit = 0u64;
+                unit |= (((m as u64) & 0x3u64) << (if cfg!(target_endian = "big") { 30 } else { 0 }));
+                unit as ::libc::c_uint
+            }
+        }
+
+        impl ::std::clone::Clone for Struct_bitfield_offsets {
+            fn clone(&self) -> Self { *self }
+        }
+
+        impl ::std::default::Default for Struct_bitfield_offsets {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+    ");
+}
+
+#[test]
+fn with_padding() {
+    assert_bind_eq("headers/struct_with_padding.h", "
+        #[repr(C, align(8))]
+        #[derive(Copy)]
+        pub struct Struct_foo {
+            pub a: ::libc::c_char,
+            pub _bindgen_padding_1_: [u8; 7usize],
+            pub b: ::libc::c_int,
+            pub _bindgen_data_1_: [u32; 2usize],
+        }
+        impl Struct_foo {
+            pub unsafe fn c(&mut self) -> *mut ::libc::c_char {
+                let raw: *mut u8 = ::std::mem::transmute(&self._bindgen_data_1_);
+                ::std::mem::transmute(raw.offset(0))
+            }
+            pub unsafe fn d(&mut self) -> *mut ::libc::c_int {
+                let raw: *mut u8 = ::std::mem::transmute(&self._bindgen_data_1_);
+                ::std::mem::transmute(raw.offset(4))
+            }
+        }
+        impl ::std::clone::Clone for Struct_foo {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_foo {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+    ");
+}
+
+#[test]
+fn with_alignment() {
+    assert_bind_eq("headers/struct_with_alignment.h", "
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_aligned_field {
+            pub a: ::libc::c_char,
+            pub _bindgen_padding_1_: [u8; 3usize],
+            pub b: ::libc::c_char,
+            pub c: ::libc::c_int,
+            pub _bindgen_data_1_: [u32; 2usize],
+        }
+        impl Struct_aligned_field {
+            pub unsafe fn d(&mut self) -> *mut ::libc::c_char {
+                let raw: *mut u8 = ::std::mem::transmute(&self._bindgen_data_1_);
+                ::std::mem::transmute(raw.offset(0))
+            }
+            pub unsafe fn e(&mut self) -> *mut ::libc::c_int {
+                let raw: *mut u8 = ::std::mem::transmute(&self._bindgen_data_1_);
+                ::std::mem::transmute(raw.offset(4))
+            }
+        }
+        impl ::std::clone::Clone for Struct_aligned_field {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_aligned_field {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        #[repr(C, packed(2))]
+        #[derive(Copy)]
+        pub struct Struct_pack2 {
+            pub a: ::libc::c_char,
+            pub b: ::libc::c_int,
+            pub c: ::libc::c_char,
+        }
+        impl ::std::clone::Clone for Struct_pack2 {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_pack2 {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        #[repr(C, align(16))]
+        #[derive(Copy)]
+        pub struct Struct_aligned16 {
+            pub a: ::libc::c_int,
+        }
+        impl ::std::clone::Clone for Struct_aligned16 {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_aligned16 {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+    ");
+}
+
+#[test]
+fn packed() {
+    assert_bind_eq("headers/struct_packed.h", "
+        #[repr(C, packed)]
+        #[derive(Copy)]
+        pub struct Struct_foo {
+            pub a: ::libc::c_char,
+            pub b: ::libc::c_int,
+        }
+        impl ::std::clone::Clone for Struct_foo {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_foo {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+    ");
+}
+
+#[test]
+fn with_int128_and_complex() {
+    assert_bind_eq("headers/struct_with_int128_and_complex.h", "
+        #[doc = \"Storage for a C `long double`, which has no Rust equivalent.\"]
+        #[repr(C, align(16))]
+        #[derive(Copy)]
+        pub struct __BindgenLongDouble {
+            pub _bindgen_data_: [u64; 2usize],
+        }
+        impl ::std::clone::Clone for __BindgenLongDouble {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for __BindgenLongDouble {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        /// A C `_Complex` number with real and imaginary parts of type `T`.
+        #[repr(C)]
+        #[derive(Copy, Clone, Default)]
+        pub struct __BindgenComplex<T> {
+            pub re: T,
+            pub im: T,
+        }
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_foo {
+            pub a: i128,
+            pub b: u128,
+            pub c: __BindgenComplex<::libc::c_float>,
+            pub d: __BindgenComplex<::libc::c_double>,
+            pub e: __BindgenLongDouble,
+            pub f: __BindgenComplex<__BindgenLongDouble>,
+        }
+        impl ::std::clone::Clone for Struct_foo {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_foo {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+    ");
+}
+
 #[test]
 fn with_fwd_decl_struct() {
     assert_bind_eq("headers/forward_declared_struct.h", "
@@ -331,3 +661,34 @@ fn with_fwd_decl_struct() {
     ");
 }
 
+
+#[test]
+fn opaque() {
+    let mut options: BindgenOptions = Default::default();
+    options.opaque_types.push("hidden".to_string());
+    assert_bind_eq_with(options, "headers/struct_opaque.h", "
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_hidden {
+            pub _bindgen_opaque_blob_: [u64; 2usize],
+        }
+        impl ::std::clone::Clone for Struct_hidden {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_hidden {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+        #[repr(C)]
+        #[derive(Copy)]
+        pub struct Struct_visible {
+            pub h: Struct_hidden,
+            pub x: ::libc::c_int,
+        }
+        impl ::std::clone::Clone for Struct_visible {
+            fn clone(&self) -> Self { *self }
+        }
+        impl ::std::default::Default for Struct_visible {
+            fn default() -> Self { unsafe { ::std::mem::zeroed() } }
+        }
+    ");
+}
@@ -49,6 +49,48 @@ fn with_anon_struct_bitfield() {
                 let raw: *mut u8 = ::std::mem::transmute(&self._bindgen_data_);
                 ::std::mem::transmute(raw.offset(0))
             }
+            #[inline]
+            pub unsafe fn b(&self) -> ::libc::c_int {
+                let unit = {
+                    let raw: *const u8 = ::std::mem::transmute(&self._bindgen_data_);
+                    *(raw.offset(0) as *const ::libc::c_int) as u64
+                };
+                let shift = if cfg!(target_endian = "big") { 25 } else { 0 };
+                (((unit << (64 - 7 - shift)) as i64) >> (64 - 7)) as ::libc::c_int
+            }
+            #[inline]
+            pub unsafe fn set_b(&mut self, val: ::libc::c_int) {
+                let unit = {
+                    let raw: *const u8 = ::std::mem::transmute(&self._bindgen_data_);
+                    *(raw.offset(0) as *const ::libc::c_int) as u64
+                };
+                let shift = if cfg!(target_endian = "big") { 25 } else { 0 };
+                let mask = 0x7fu64 << shift;
+                let raw: *mut u8 = ::std::mem::transmute(&mut self._bindgen_data_);
+                *(raw.offset(0) as *mut ::libc::c_int) =
+                    ((unit & !mask) | ((val as u64) << shift & mask)) as ::libc::c_int;
+            }
+            #[inline]
+            pub unsafe fn c(&self) -> ::libc::c_int {
+                let unit = {
+                    let raw: *const u8 = ::std::mem::transmute(&self._bindgen_data_);
+                    *(raw.offset(0) as *const ::libc::c_int) as u64
+                };
+                let shift = if cfg!(target_endian = "big") { 0 } else { 7 };
+                (((unit << (64 - 25 - shift)) as i64) >> (64 - 25)) as ::libc::c_int
+            }
+            #[inline]
+            pub unsafe fn set_c(&mut self, val: ::libc::c_int) {
+                let unit = {
+                    let raw: *const u8 = ::std::mem::transmute(&self._bindgen_data_);
+                    *(raw.offset(0) as *const ::libc::c_int) as u64
+                };
+                let shift = if cfg!(target_endian = "big") { 0 } else { 7 };
+                let mask = 0x1ffffffu64 << shift;
+                let raw: *mut u8 = ::std::mem::transmute(&mut self._bindgen_data_);
+                *(raw.offset(0) as *mut ::libc::c_int) =
+                    ((unit & !mask) | ((val as u64) << shift & mask)) as ::libc::c_int;
+            }
         }
 
         impl ::std::clone::Clone for Union_foo {
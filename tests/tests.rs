@@ -8,8 +8,14 @@ mod support;
 
 // Unused until we can generate code for tests
 //mod test_cmath;
+mod test_comments;
+mod test_cxx;
 mod test_decl;
+mod test_enum;
 mod test_func;
+mod test_layout;
+mod test_macros;
+mod test_objc;
 mod test_struct;
 mod test_union;
 mod test_builtins;